@@ -0,0 +1,59 @@
+//! Measure how long reading the directory takes.
+//!
+//! Reads the CSV file given as argument or, without one, a generated file
+//! with 120000 rows:
+//!
+//! ```sh
+//! cargo run --release --example bench_read [wwff_directory.csv]
+//! ```
+
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 120_000;
+const ROUNDS: usize = 10;
+
+fn main() {
+    let csv = match std::env::args().nth(1) {
+        Some(path) => std::fs::read(path).unwrap(),
+        None => generate(ROWS),
+    };
+
+    // Warm up
+    let dir = wwff_directory::from_reader(csv.as_slice()).unwrap();
+
+    let mut times: Vec<Duration> = (0..ROUNDS)
+        .map(|_| {
+            let ts = Instant::now();
+            wwff_directory::from_reader(csv.as_slice()).unwrap();
+            ts.elapsed()
+        })
+        .collect();
+    times.sort();
+
+    println!(
+        "entries: {}, min: {:?}, median: {:?}",
+        dir.sorted_entries().len(),
+        times[0],
+        times[ROUNDS / 2]
+    );
+}
+
+/// Generate CSV file with given number of rows resembling the official one
+fn generate(rows: usize) -> Vec<u8> {
+    let mut csv = String::from(
+        "reference,status,name,program,dxcc,state,county,continent,iota,iaruLocator,\
+         latitude,longitude,IUCNcat,validFrom,validTo,notes,lastMod,changeLog,reviewFlag,\
+         specialFlags,website,country,region,dxccEnum,qsoCount,lastAct\n",
+    );
+    for i in 0..rows {
+        let program = ["ONFF", "OHFF", "DLFF", "KFF", "VKFF"][i % 5];
+        let latitude = -60.0 + (i % 1200) as f32 / 10.0;
+        let longitude = -180.0 + (i % 3600) as f32 / 10.0;
+        csv.push_str(&format!(
+            "{program}-{i:04},active,Nature Park {i},{program},{program},,,EU,,JO20AA,\
+             {latitude:.4},{longitude:.4},II,2012-01-01,,,2020-05-17,,0,,\
+             https://example.com/{i},Country,Region,209,{i},2023-06-01\n",
+        ));
+    }
+    csv.into_bytes()
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use reqwest::blocking::Client;
+use tracing::instrument;
+
+use crate::http::{read_to_cache, DownloadStats, Downloader};
+use crate::{DownloadBuilder, Parsed, WwffDirectory, WwffError};
+
+impl DownloadBuilder {
+    /// Download WWFF directory with this configuration without async
+    /// runtime.
+    ///
+    /// Updates with [WwffDirectory::try_download_update_blocking] use the
+    /// same configuration.
+    #[instrument]
+    pub fn download_blocking(self) -> Result<WwffDirectory, WwffError> {
+        WwffDirectory::from_download_blocking_with(self.build())
+    }
+}
+
+/// Synchronous counterpart of async download
+impl Downloader {
+    #[instrument(skip(self))]
+    pub fn download_blocking(&mut self) -> Result<Option<Parsed>, WwffError> {
+        let mut backoff = self.backoff();
+        loop {
+            match self.download_blocking_once() {
+                Err(err) => match backoff.retry(&err) {
+                    Some(delay) => std::thread::sleep(delay),
+                    None => return Err(err),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn download_blocking_once(&mut self) -> Result<Option<Parsed>, WwffError> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let client = match &self.blocking_client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder()
+                    .user_agent(&self.user_agent)
+                    .connect_timeout(self.connect_timeout)
+                    // Timeout of blocking client applies separately to
+                    // waiting for the response and to each read of the body.
+                    // Timeout of the whole request is checked while reading.
+                    .timeout(self.read_timeout);
+                if let Some(proxy) = self.proxy.clone() {
+                    builder = builder.proxy(proxy);
+                }
+                if self.accept_invalid_certs {
+                    tracing::warn!("TLS certificate verification is disabled");
+                    builder = builder.danger_accept_invalid_certs(true);
+                }
+                self.blocking_client.insert(builder.build()?)
+            }
+        };
+
+        let resp = client
+            .get(&self.url)
+            .headers(self.conditional_headers())
+            .send()?;
+
+        if !self.check_status(resp.status(), resp.url())? {
+            return Ok(None);
+        }
+
+        let headers = resp.headers().clone();
+
+        // Response body is parsed while it's being downloaded
+        let bytes = Arc::new(AtomicU64::new(0));
+        let reader = CountingReader {
+            reader: resp,
+            bytes: bytes.clone(),
+            deadline,
+        };
+        let parsed = match &self.cache_path {
+            Some(path) => read_to_cache(reader, path, &self.options)?,
+            None => crate::read(reader, &self.options)?,
+        };
+
+        self.downloaded(
+            &headers,
+            DownloadStats {
+                bytes: bytes.load(Ordering::Relaxed),
+                records: parsed.map.len(),
+                not_modified: false,
+            },
+        );
+
+        Ok(Some(parsed))
+    }
+}
+
+/// Reader which counts the bytes read through it and fails after deadline
+struct CountingReader<R> {
+    reader: R,
+    bytes: Arc<AtomicU64>,
+    deadline: Option<Instant>,
+}
+
+impl<R: std::io::Read> std::io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "Download took longer than the timeout",
+                ));
+            }
+        }
+        let n = self.reader.read(buf)?;
+        self.bytes.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
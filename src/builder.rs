@@ -0,0 +1,178 @@
+//! Builder for configuring how WWFF directory is read
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{read, Entry, ParseWarning, WwffDirectory, WwffError};
+
+/// Builder for configuring how WWFF directory is read
+///
+/// Created with [WwffDirectory::builder].
+#[derive(Debug, Default, Clone)]
+pub struct DirectoryBuilder {
+    options: ReadOptions,
+}
+
+/// Options used when reading CSV file
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ReadOptions {
+    skip_notes: bool,
+    skip_changelog: bool,
+    skip_special_flags: bool,
+    skip_website: bool,
+    raw_text: bool,
+    dxcc_filter: Option<Vec<String>>,
+    warning_sink: Option<WarningSink>,
+    entry_filter: Option<EntryFilter>,
+}
+
+/// Callback for warnings found while reading
+#[derive(Clone)]
+struct WarningSink(Arc<dyn Fn(&ParseWarning) + Send + Sync>);
+
+impl std::fmt::Debug for WarningSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WarningSink")
+    }
+}
+
+/// Predicate for entries to keep
+#[derive(Clone)]
+struct EntryFilter(Arc<dyn Fn(&Entry) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for EntryFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EntryFilter")
+    }
+}
+
+impl ReadOptions {
+    /// Keep only entries for which `filter` returns true
+    pub(crate) fn set_entry_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(&Entry) -> bool + Send + Sync + 'static,
+    {
+        self.entry_filter = Some(EntryFilter(Arc::new(filter)));
+    }
+
+    /// Check if parsed entry should be kept
+    pub(crate) fn keeps_entry(&self, entry: &Entry) -> bool {
+        self.entry_filter
+            .as_ref()
+            .is_none_or(|EntryFilter(filter)| filter(entry))
+    }
+
+    /// Check if column with given header should be left unparsed
+    pub(crate) fn skips(&self, header: &str) -> bool {
+        match header {
+            "notes" => self.skip_notes,
+            "changeLog" => self.skip_changelog,
+            "specialFlags" => self.skip_special_flags,
+            "website" => self.skip_website,
+            _ => false,
+        }
+    }
+
+    /// Check if free text fields should be normalized after parsing
+    pub(crate) fn normalizes_text(&self) -> bool {
+        !self.raw_text
+    }
+
+    /// Pass warning to the sink or log it if there's no sink
+    pub(crate) fn warn(&self, warning: ParseWarning) {
+        if let Some(WarningSink(sink)) = &self.warning_sink {
+            sink(&warning);
+            return;
+        }
+
+        warning.log();
+    }
+
+    /// Check if row with given DXCC prefix should be kept
+    pub(crate) fn keeps_dxcc(&self, dxcc: Option<&str>) -> bool {
+        let Some(filter) = &self.dxcc_filter else {
+            return true;
+        };
+        dxcc.is_some_and(|dxcc| filter.iter().any(|d| d.eq_ignore_ascii_case(dxcc)))
+    }
+}
+
+impl DirectoryBuilder {
+    /// Don't parse `notes` field. It's left empty.
+    pub fn skip_notes(mut self) -> Self {
+        self.options.skip_notes = true;
+        self
+    }
+
+    /// Don't parse `changelog` field. It's left [None].
+    pub fn skip_changelog(mut self) -> Self {
+        self.options.skip_changelog = true;
+        self
+    }
+
+    /// Don't parse `special_flags` field. It's left [None].
+    pub fn skip_special_flags(mut self) -> Self {
+        self.options.skip_special_flags = true;
+        self
+    }
+
+    /// Don't parse `website` field. It's left [None].
+    pub fn skip_website(mut self) -> Self {
+        self.options.skip_website = true;
+        self
+    }
+
+    /// Keep `name` and `notes` as they are in the CSV file.
+    ///
+    /// By default leading and trailing whitespace is trimmed, internal
+    /// whitespace is collapsed into single spaces and control and zero-width
+    /// characters are removed.
+    pub fn raw_text(mut self) -> Self {
+        self.options.raw_text = true;
+        self
+    }
+
+    /// Only keep entries whose `dxcc` is one of the given prefixes, e.g.
+    /// `&["K", "VE"]`. Other rows are dropped before they are parsed.
+    pub fn dxcc_filter(mut self, dxcc: &[&str]) -> Self {
+        self.options.dxcc_filter = Some(dxcc.iter().map(|d| d.to_string()).collect());
+        self
+    }
+
+    /// Keep only entries for which `predicate` returns true. Other rows are
+    /// dropped right after they are parsed.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Entry) -> bool + Send + Sync + 'static,
+    {
+        self.options.set_entry_filter(predicate);
+        self
+    }
+
+    /// Call `on_warning` for each problem found while reading, e.g. to show
+    /// them in own UI. The warnings are then not logged with `tracing`.
+    ///
+    /// Skipped and duplicate rows are also available from
+    /// [WwffDirectory::parse_report] after reading.
+    pub fn on_warning<F>(mut self, on_warning: F) -> Self
+    where
+        F: Fn(&ParseWarning) + Send + Sync + 'static,
+    {
+        self.options.warning_sink = Some(WarningSink(Arc::new(on_warning)));
+        self
+    }
+
+    /// Read CSV file from given [Path]
+    pub fn from_path<P: AsRef<Path>>(self, path: P) -> Result<WwffDirectory, WwffError> {
+        let parsed = read(File::open(path)?, &self.options)?;
+        Ok(WwffDirectory::from_parsed(parsed))
+    }
+
+    /// Read CSV file from given reader
+    pub fn from_reader<R: Read>(self, reader: R) -> Result<WwffDirectory, WwffError> {
+        let parsed = read(reader, &self.options)?;
+        Ok(WwffDirectory::from_parsed(parsed))
+    }
+}
@@ -0,0 +1,57 @@
+//! Structured view of entry's changelog
+
+use chrono::NaiveDate;
+
+use crate::Entry;
+
+/// Date formats seen at the start of changelog lines
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d.%m.%Y", "%Y/%m/%d"];
+
+/// A single line of entry's changelog
+///
+/// Created with [Entry::parsed_changelog].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeLogEntry {
+    /// Date at the start of the line, if it could be parsed
+    pub date: Option<NaiveDate>,
+    /// Rest of the line
+    pub text: String,
+}
+
+impl Entry {
+    /// Split `changelog` into lines with optional leading date.
+    ///
+    /// This is best-effort: lines which don't start with a recognized date
+    /// are kept as is with `date` set to [None]. Empty lines are skipped.
+    pub fn parsed_changelog(&self) -> Vec<ChangeLogEntry> {
+        let Some(changelog) = &self.changelog else {
+            return Vec::new();
+        };
+
+        changelog
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(parse_line)
+            .collect()
+    }
+}
+
+fn parse_line(line: &str) -> ChangeLogEntry {
+    let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let first = first.trim_end_matches([':', ',', ';']);
+
+    match DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(first, f).ok())
+    {
+        Some(date) => ChangeLogEntry {
+            date: Some(date),
+            text: rest.trim_start_matches([' ', '-', ':']).trim().to_string(),
+        },
+        None => ChangeLogEntry {
+            date: None,
+            text: line.to_string(),
+        },
+    }
+}
@@ -0,0 +1,61 @@
+//! Differences between two WWFF directories
+
+use std::collections::BTreeSet;
+
+use tracing::instrument;
+
+use crate::{Reference, WwffDirectory, WwffMap};
+
+/// Differences between two versions of WWFF directory
+///
+/// Created with [WwffDirectory::diff].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirectoryDiff {
+    /// References found only in the newer directory
+    pub added: BTreeSet<Reference>,
+    /// References found only in the older directory
+    pub removed: BTreeSet<Reference>,
+    /// References found in both directories but with different entries
+    pub modified: BTreeSet<Reference>,
+}
+
+impl DirectoryDiff {
+    /// Check if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl WwffDirectory {
+    /// Compare this directory to a newer version of it.
+    ///
+    /// Entries are compared field by field.
+    #[instrument(skip_all)]
+    pub fn diff(&self, other: &WwffDirectory) -> DirectoryDiff {
+        diff(&self.map, &other.map)
+    }
+}
+
+pub(crate) fn diff(old: &WwffMap, new: &WwffMap) -> DirectoryDiff {
+    let mut diff = DirectoryDiff::default();
+
+    for (reference, entry) in old {
+        match new.get(reference) {
+            None => {
+                diff.removed.insert(*reference);
+            }
+            Some(new_entry) if new_entry != entry => {
+                diff.modified.insert(*reference);
+            }
+            Some(_) => (),
+        }
+    }
+
+    diff.added = new
+        .keys()
+        .filter(|reference| !old.contains_key(*reference))
+        .copied()
+        .collect();
+
+    diff
+}
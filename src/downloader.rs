@@ -1,92 +1,275 @@
+use std::future::Future;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use reqwest::header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
-use thiserror::Error;
+use futures_util::stream;
+use reqwest::header::HeaderValue;
+use tokio_util::io::StreamReader;
 use tracing::instrument;
 
-use crate::WwffMap;
+use crate::builder::ReadOptions;
+use crate::http::{read_timed_out, read_to_cache, CacheState, DownloadStats, Downloader};
+use crate::{DownloadBuilder, Parsed, WwffDirectory, WwffError};
 
-const WWFF_DIRECTORY_URL: &str = "https://wwff.co/wwff-data/wwff_directory.csv";
-const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+impl DownloadBuilder {
+    /// Download WWFF directory with this configuration.
+    ///
+    /// Updates with [WwffDirectory::try_download_update] use the same
+    /// configuration.
+    #[instrument]
+    pub async fn download(self) -> Result<WwffDirectory, WwffError> {
+        WwffDirectory::from_download_with(self.build()).await
+    }
+
+    /// Read WWFF directory from a local copy in given [Path] or download it
+    /// with this configuration if there's no local copy.
+    ///
+    /// See [WwffDirectory::from_cache_or_download].
+    #[instrument(fields(path = %path.as_ref().to_string_lossy()))]
+    pub async fn from_cache_or_download<P: AsRef<Path>>(
+        self,
+        path: P,
+    ) -> Result<WwffDirectory, WwffError> {
+        WwffDirectory::from_cache_or_download_with(self.build(), path.as_ref()).await
+    }
+
+    /// Create [WwffDownloader] with this configuration for fetching the CSV
+    /// file without parsing it. [DownloadBuilder::filter] has no effect on
+    /// it.
+    pub fn into_downloader(self) -> WwffDownloader {
+        WwffDownloader(self.build())
+    }
+}
 
+/// Downloader for fetching the WWFF directory CSV file as is, e.g. to archive
+/// it, without parsing it into [WwffDirectory].
+///
+/// Like [WwffDirectory::try_download_update] this makes conditional requests
+/// using the `ETag` and `Last-Modified` headers of the previous successful
+/// download. Configure it with [DownloadBuilder::into_downloader].
 #[derive(Debug)]
-pub(crate) struct Downloader {
-    client: reqwest::Client,
-    last_modified: Option<HeaderValue>,
-    etag: Option<HeaderValue>,
+pub struct WwffDownloader(Downloader);
+
+impl WwffDownloader {
+    /// Create downloader for the original source with default configuration.
+    pub fn new() -> Self {
+        Self(Downloader::new())
+    }
+
+    /// Download the CSV file if it has changed since the previous successful
+    /// download.
+    ///
+    /// Returns the response body or [None] if the server responded that the
+    /// file wasn't modified. The body is not decompressed if the file itself
+    /// is gzip-compressed.
+    #[instrument(skip(self))]
+    pub async fn fetch_raw(&mut self) -> Result<Option<Vec<u8>>, WwffError> {
+        self.0.fetch_raw().await
+    }
+
+    /// Get HTTP cache validators of the last successful download.
+    pub fn cache_state(&self) -> CacheState {
+        self.0.cache_state()
+    }
+
+    /// Restore HTTP cache validators previously saved with
+    /// [WwffDownloader::cache_state].
+    pub fn set_cache_state(&mut self, state: CacheState) {
+        self.0.set_cache_state(state);
+    }
+
+    /// Get statistics of the last successful download or update check.
+    ///
+    /// `records` is always zero as the body isn't parsed.
+    pub fn last_stats(&self) -> Option<DownloadStats> {
+        self.0.last_stats()
+    }
+}
+
+impl Default for WwffDownloader {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Downloader {
     #[instrument]
-    pub fn new() -> Self {
-        let client = reqwest::ClientBuilder::new()
-            .user_agent(USER_AGENT)
-            .build()
-            .unwrap();
-
-        Self {
-            client,
-            last_modified: None,
-            etag: None,
-        }
+    pub fn with_url(url: &str) -> Self {
+        DownloadBuilder::default().url(url).build()
+    }
+
+    pub fn read_options(&self) -> &ReadOptions {
+        &self.options
+    }
+
+    pub fn set_cache_path(&mut self, path: &Path) {
+        self.cache_path = Some(path.to_path_buf());
+    }
+
+    /// Only download if modified since given time
+    pub fn set_modified_since(&mut self, time: SystemTime) {
+        self.modified_since = HeaderValue::from_str(&httpdate::fmt_http_date(time)).ok();
     }
 
     #[instrument(skip(self))]
-    pub async fn download(&mut self) -> Result<Option<WwffMap>, DownloaderError> {
-        let client = &self.client;
+    pub async fn download(&mut self) -> Result<Option<Parsed>, WwffError> {
+        self.with_retries(|d| Box::pin(d.download_once())).await
+    }
 
-        let mut request = client.get(WWFF_DIRECTORY_URL);
+    /// Download the CSV file without parsing it
+    pub async fn fetch_raw(&mut self) -> Result<Option<Vec<u8>>, WwffError> {
+        self.with_retries(|d| Box::pin(d.fetch_raw_once())).await
+    }
 
-        if let Some(last_modified) = &self.last_modified {
-            tracing::debug!("Adding If-Modified-Since header: {last_modified:?}");
-            request = request.header(IF_MODIFIED_SINCE, last_modified);
+    async fn with_retries<T>(
+        &mut self,
+        f: for<'a> fn(&'a mut Self) -> Attempt<'a, T>,
+    ) -> Result<Option<T>, WwffError> {
+        let mut backoff = self.backoff();
+        loop {
+            match f(self).await {
+                Err(err) => match backoff.retry(&err) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(err),
+                },
+                result => return result,
+            }
         }
+    }
 
-        if let Some(etag) = &self.etag {
-            tracing::debug!("Adding If-None-Match header: {etag:?}");
-            request = request.header(IF_NONE_MATCH, etag);
-        }
+    async fn download_once(&mut self) -> Result<Option<Parsed>, WwffError> {
+        let Some(resp) = self.send().await? else {
+            return Ok(None);
+        };
+
+        let headers = resp.headers().clone();
+
+        // Parse the body while it's being downloaded instead of buffering
+        // the whole file into memory first
+        let bytes = Arc::new(AtomicU64::new(0));
+        let counter = bytes.clone();
+        let read_timeout = self.read_timeout;
+        let body = stream::try_unfold(resp, move |mut resp| {
+            let counter = counter.clone();
+            async move {
+                let chunk = next_chunk(&mut resp, read_timeout).await?;
+                if let Some(chunk) = &chunk {
+                    counter.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                }
+                Ok::<_, io::Error>(chunk.map(|chunk| (chunk, resp)))
+            }
+        });
+        let reader = StreamReader::new(Box::pin(body));
+        let parsed = match self.cache_path.clone() {
+            Some(path) => read_async_to_cache(reader, path, self.options.clone()).await?,
+            None => crate::read_async(reader, self.options.clone()).await?,
+        };
 
-        let resp = request.send().await?;
+        self.downloaded(
+            &headers,
+            DownloadStats {
+                bytes: bytes.load(Ordering::Relaxed),
+                records: parsed.map.len(),
+                not_modified: false,
+            },
+        );
 
-        // Not modified since last request
-        if resp.status() == 304 {
-            tracing::debug!("wwff_directory.csv not modified. Bandwidth saved.");
+        Ok(Some(parsed))
+    }
+
+    async fn fetch_raw_once(&mut self) -> Result<Option<Vec<u8>>, WwffError> {
+        let Some(mut resp) = self.send().await? else {
             return Ok(None);
-        }
+        };
 
-        if resp.status() != 200 {
-            tracing::debug!("Some error {}", resp.status());
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("HTTP response returned status code {}", resp.status()),
-            )
-            .into());
+        let headers = resp.headers().clone();
+        let mut body = Vec::new();
+        while let Some(chunk) = next_chunk(&mut resp, self.read_timeout).await? {
+            body.extend_from_slice(&chunk);
         }
 
-        self.last_modified = resp.headers().get(LAST_MODIFIED).cloned();
-        self.etag = resp.headers().get(ETAG).cloned();
+        self.downloaded(
+            &headers,
+            DownloadStats {
+                bytes: body.len() as u64,
+                records: 0,
+                not_modified: false,
+            },
+        );
+
+        Ok(Some(body))
+    }
+
+    /// Send conditional request. Returns [None] if the directory wasn't
+    /// modified.
+    async fn send(&mut self) -> Result<Option<reqwest::Response>, WwffError> {
+        let client = match &self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::Client::builder()
+                    .user_agent(&self.user_agent)
+                    .connect_timeout(self.connect_timeout);
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(proxy) = self.proxy.clone() {
+                    builder = builder.proxy(proxy);
+                }
+                if self.accept_invalid_certs {
+                    tracing::warn!("TLS certificate verification is disabled");
+                    builder = builder.danger_accept_invalid_certs(true);
+                }
+                self.client.insert(builder.build()?)
+            }
+        };
 
-        let text = resp.text().await?;
-        let wwff_map = crate::read(csv::Reader::from_reader(text.as_bytes()))?;
+        let request = client
+            .get(&self.url)
+            .headers(self.conditional_headers())
+            .send();
+        let resp = tokio::time::timeout(self.read_timeout, request)
+            .await
+            .map_err(|_| read_timed_out(self.read_timeout))??;
 
-        Ok(Some(wwff_map))
+        if !self.check_status(resp.status(), resp.url())? {
+            return Ok(None);
+        }
+
+        Ok(Some(resp))
     }
 }
 
-#[derive(Error, Debug)]
-pub(crate) enum DownloaderError {
-    #[error("IO error")]
-    Io(#[from] io::Error),
-    #[error("HTTP error")]
-    Http(#[from] reqwest::Error),
+/// Get next chunk of response body. Fails if the server sends nothing within
+/// `timeout`.
+async fn next_chunk(
+    resp: &mut reqwest::Response,
+    timeout: Duration,
+) -> Result<Option<bytes::Bytes>, io::Error> {
+    tokio::time::timeout(timeout, resp.chunk())
+        .await
+        .map_err(|_| read_timed_out(timeout))?
+        .map_err(io::Error::other)
 }
 
-impl From<DownloaderError> for io::Error {
-    fn from(err: DownloaderError) -> Self {
-        match err {
-            DownloaderError::Io(err) => err,
-            DownloaderError::Http(err) => io::Error::new(io::ErrorKind::Other, err),
-        }
-    }
+/// A single download attempt
+type Attempt<'a, T> = Pin<Box<dyn Future<Output = Result<Option<T>, WwffError>> + Send + 'a>>;
+
+/// Read CSV from async reader on a blocking thread while writing a copy of it
+/// to given path. See [read_to_cache].
+async fn read_async_to_cache<R>(
+    reader: R,
+    path: PathBuf,
+    options: ReadOptions,
+) -> Result<Parsed, WwffError>
+where
+    R: tokio::io::AsyncRead + Send + Unpin + 'static,
+{
+    let reader = tokio_util::io::SyncIoBridge::new(reader);
+    tokio::task::spawn_blocking(move || read_to_cache(reader, &path, &options))
+        .await
+        .map_err(io::Error::other)?
 }
@@ -0,0 +1,122 @@
+use std::io;
+
+use thiserror::Error;
+
+/// Errors returned when reading or downloading WWFF directory
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum WwffError {
+    /// Reading the directory failed
+    #[error("IO error")]
+    Io(#[source] io::Error),
+    /// The directory is not valid CSV
+    #[error("CSV error")]
+    Csv(#[source] csv::Error),
+    /// Downloading the directory failed
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    #[error("HTTP error")]
+    Http(#[from] reqwest::Error),
+    /// Server responded with other status than `200 OK` or `304 Not Modified`
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    #[error("HTTP status {status} from {url}")]
+    HttpStatus {
+        /// Status code of the response
+        status: reqwest::StatusCode,
+        /// Final URL of the request after redirects
+        url: String,
+    },
+    /// Reading or writing snapshot failed
+    #[cfg(feature = "bincode")]
+    #[error("Snapshot error")]
+    Snapshot(#[from] bincode::Error),
+    /// The directory is valid CSV but its content couldn't be parsed
+    #[error("Parse error: {0}")]
+    Parse(String),
+}
+
+impl WwffError {
+    /// Check if the error may go away by trying again, i.e. it's a timeout,
+    /// a connection error or a `5xx` or `429 Too Many Requests` status.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            WwffError::Io(err) => err.kind() == io::ErrorKind::TimedOut,
+            WwffError::Http(err) => {
+                err.is_timeout() || err.is_connect() || err.is_request() || err.is_body()
+            }
+            WwffError::HttpStatus { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Display error followed by its sources, e.g. `"HTTP error: error sending
+/// request: connection refused"`, for logging
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+pub(crate) struct ErrorChain<'a>(pub &'a dyn std::error::Error);
+
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+impl std::fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(err) = source {
+            write!(f, ": {err}")?;
+            source = err.source();
+        }
+        Ok(())
+    }
+}
+
+impl From<io::Error> for WwffError {
+    fn from(err: io::Error) -> Self {
+        // Errors of downloading the body are passed through CSV reader as IO
+        // errors
+        #[cfg(any(feature = "downloader", feature = "blocking"))]
+        if err.get_ref().is_some_and(|e| e.is::<reqwest::Error>()) {
+            let inner = err.into_inner().expect("checked above");
+            return WwffError::Http(*inner.downcast().expect("checked above"));
+        }
+
+        WwffError::Io(err)
+    }
+}
+
+impl From<csv::Error> for WwffError {
+    fn from(err: csv::Error) -> Self {
+        if err.is_io_error() {
+            match err.into_kind() {
+                csv::ErrorKind::Io(err) => err.into(),
+                _ => unreachable!("csv error is io error"),
+            }
+        } else {
+            WwffError::Csv(err)
+        }
+    }
+}
+
+impl From<WwffError> for io::Error {
+    fn from(err: WwffError) -> Self {
+        match err {
+            WwffError::Io(err) => err,
+            err => io::Error::other(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::io;
+
+    use super::WwffError;
+
+    #[test]
+    fn keeps_cause_as_source() {
+        let err = WwffError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(err.to_string(), "IO error");
+        assert_eq!(err.source().unwrap().to_string(), "no such file");
+    }
+}
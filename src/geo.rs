@@ -0,0 +1,221 @@
+//! Geographic helpers
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use tinystr::TinyAsciiStr;
+
+use crate::{Entry, WwffDirectory};
+
+/// Mean radius of Earth in kilometers
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+impl WwffDirectory {
+    /// Great-circle distance in kilometers between two references.
+    ///
+    /// Returns [None] if either of the references is not found or lacks
+    /// coordinates.
+    pub fn distance_km(&self, a: &str, b: &str) -> Option<f32> {
+        let a = self.search_reference(a)?;
+        let b = self.search_reference(b)?;
+        let (latitude, longitude) = a.coordinates()?;
+        b.distance_from(latitude, longitude)
+    }
+
+    /// Find `k` entries nearest to given coordinates.
+    ///
+    /// Returns entries together with their distance in kilometers, nearest
+    /// first. See [WwffDirectory::nearest_paged].
+    pub fn nearest(&self, latitude: f32, longitude: f32, k: usize) -> Vec<(&Entry, f32)> {
+        self.nearest_paged(latitude, longitude, 0, k)
+    }
+
+    /// Find entries nearest to given coordinates, skipping first `offset`
+    /// entries and returning at most `limit` entries.
+    ///
+    /// Returns entries together with their distance in kilometers, nearest
+    /// first. Entries without coordinates are skipped. Entries at equal
+    /// distance are ordered by reference so results are stable across calls
+    /// with the same arguments.
+    ///
+    /// Distance is computed to every entry and only the first `offset + limit`
+    /// entries are sorted, so this is O(n + k log k) where k is
+    /// `offset + limit`.
+    pub fn nearest_paged(
+        &self,
+        latitude: f32,
+        longitude: f32,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<(&Entry, f32)> {
+        let mut entries = self.distances_from(latitude, longitude);
+
+        let end = offset.saturating_add(limit);
+        if end < entries.len() {
+            entries.select_nth_unstable_by(end, by_distance);
+            entries.truncate(end);
+        }
+        entries.sort_by(by_distance);
+
+        entries.into_iter().skip(offset).collect()
+    }
+
+    /// Find all entries within `radius_km` kilometers from given coordinates.
+    ///
+    /// Returns entries together with their distance in kilometers, nearest
+    /// first. Entries without coordinates are skipped and entries at equal
+    /// distance are ordered by reference.
+    pub fn within_radius(
+        &self,
+        latitude: f32,
+        longitude: f32,
+        radius_km: f32,
+    ) -> Vec<(&Entry, f32)> {
+        let mut entries = self.distances_from(latitude, longitude);
+        entries.retain(|(_, distance)| *distance <= radius_km);
+        entries.sort_by(by_distance);
+        entries
+    }
+
+    /// Find the entry nearest to given coordinates if it's within
+    /// `threshold_km` kilometers, e.g. to tell which park the user is
+    /// probably at.
+    ///
+    /// The directory only has a single point per park, not its boundaries, so
+    /// this is not a containment test: inside a large park the nearest point
+    /// may be far away or belong to a neighbouring park. Choose the threshold
+    /// accordingly and treat the result as a guess.
+    pub fn containing_or_nearest(
+        &self,
+        latitude: f32,
+        longitude: f32,
+        threshold_km: f32,
+    ) -> Option<&Entry> {
+        self.distances_from(latitude, longitude)
+            .into_iter()
+            .filter(|(_, distance)| *distance <= threshold_km)
+            .min_by(by_distance)
+            .map(|(entry, _)| entry)
+    }
+
+    /// Get the bounding box of all entries with coordinates as
+    /// `(min_lat, min_lon, max_lat, max_lon)`.
+    ///
+    /// Returns [None] if no entries have coordinates. See
+    /// [WwffDirectory::bounds_filtered].
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        self.bounds_filtered(|_| true)
+    }
+
+    /// Get the bounding box of entries with coordinates matching `filter` as
+    /// `(min_lat, min_lon, max_lat, max_lon)`.
+    ///
+    /// Returns [None] if no matching entries have coordinates. The box doesn't
+    /// wrap around the antimeridian.
+    pub fn bounds_filtered<F>(&self, mut filter: F) -> Option<(f32, f32, f32, f32)>
+    where
+        F: FnMut(&Entry) -> bool,
+    {
+        self.map
+            .values()
+            .filter(|e| filter(e))
+            .filter_map(Entry::coordinates)
+            .fold(None, |bounds, (lat, lon)| {
+                Some(match bounds {
+                    None => (lat, lon, lat, lon),
+                    Some((min_lat, min_lon, max_lat, max_lon)) => (
+                        min_lat.min(lat),
+                        min_lon.min(lon),
+                        max_lat.max(lat),
+                        max_lon.max(lon),
+                    ),
+                })
+            })
+    }
+
+    /// Get the centroid of entries with coordinates per DXCC as latitude and
+    /// longitude, e.g. for drawing one marker per country.
+    ///
+    /// The centroid is an unweighted arithmetic mean of the coordinates, so
+    /// it may be far off for DXCCs spanning the antimeridian. Entries without
+    /// coordinates or DXCC are skipped.
+    pub fn centroid_by_dxcc(&self) -> BTreeMap<TinyAsciiStr<8>, (f32, f32)> {
+        self.centroids(|e| Some(e.dxcc).filter(|d| !d.is_empty()))
+    }
+
+    /// Get the centroid of entries with coordinates per continent as latitude
+    /// and longitude.
+    ///
+    /// Computed like [WwffDirectory::centroid_by_dxcc]. Entries without
+    /// continent are skipped.
+    pub fn centroid_by_continent(&self) -> BTreeMap<TinyAsciiStr<2>, (f32, f32)> {
+        self.centroids(|e| Some(e.continent).filter(|c| !c.is_empty()))
+    }
+
+    /// Mean coordinates of entries grouped by given key
+    fn centroids<K, F>(&self, key: F) -> BTreeMap<K, (f32, f32)>
+    where
+        K: Ord,
+        F: Fn(&Entry) -> Option<K>,
+    {
+        let mut sums: BTreeMap<_, (f64, f64, u32)> = BTreeMap::new();
+        for e in self.map.values() {
+            let (Some(key), Some((lat, lon))) = (key(e), e.coordinates()) else {
+                continue;
+            };
+            let sum = sums.entry(key).or_default();
+            sum.0 += f64::from(lat);
+            sum.1 += f64::from(lon);
+            sum.2 += 1;
+        }
+
+        sums.into_iter()
+            .map(|(key, (lat, lon, n))| {
+                let n = f64::from(n);
+                (key, ((lat / n) as f32, (lon / n) as f32))
+            })
+            .collect()
+    }
+
+    /// Distances in kilometers from given coordinates to all entries with
+    /// coordinates
+    fn distances_from(&self, latitude: f32, longitude: f32) -> Vec<(&Entry, f32)> {
+        self.map
+            .values()
+            .filter_map(|e| Some((e, e.distance_from(latitude, longitude)?)))
+            .collect()
+    }
+}
+
+impl Entry {
+    /// Distance in kilometers from given coordinates to the entry. Returns
+    /// [None] if the entry has no coordinates, see [Entry::coordinates].
+    pub(crate) fn distance_from(&self, latitude: f32, longitude: f32) -> Option<f32> {
+        let (entry_latitude, entry_longitude) = self.coordinates()?;
+        Some(distance_km(
+            latitude,
+            longitude,
+            entry_latitude,
+            entry_longitude,
+        ))
+    }
+}
+
+/// Order by distance and then by reference
+fn by_distance(a: &(&Entry, f32), b: &(&Entry, f32)) -> Ordering {
+    a.1.total_cmp(&b.1)
+        .then_with(|| a.0.reference.cmp(&b.0.reference))
+}
+
+/// Great-circle distance in kilometers between two points using the haversine
+/// formula.
+fn distance_km(lat1: f32, lon1: f32, lat2: f32, lon2: f32) -> f32 {
+    let (lat1, lon1) = (f64::from(lat1).to_radians(), f64::from(lon1).to_radians());
+    let (lat2, lon2) = (f64::from(lat2).to_radians(), f64::from(lon2).to_radians());
+
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().asin();
+
+    (EARTH_RADIUS_KM * c) as f32
+}
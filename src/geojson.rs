@@ -0,0 +1,78 @@
+//! Export WWFF directory as GeoJSON
+
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{Entry, Reference, Status, WwffDirectory};
+
+impl WwffDirectory {
+    /// Export entries as [GeoJSON](https://geojson.org/) FeatureCollection.
+    ///
+    /// Each entry with coordinates becomes a Point feature with properties
+    /// `reference`, `name`, `program`, `dxcc` and `status`. Entries without
+    /// coordinates are skipped.
+    #[instrument(skip(self))]
+    pub fn to_geojson(&self) -> String {
+        let features = self.map.values().filter_map(Feature::from_entry).collect();
+
+        let collection = FeatureCollection {
+            kind: "FeatureCollection",
+            features,
+        };
+
+        // Plain structs with string keys always serialize successfully
+        serde_json::to_string(&collection).expect("GeoJSON serialization failed")
+    }
+}
+
+#[derive(Serialize)]
+struct FeatureCollection<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    features: Vec<Feature<'a>>,
+}
+
+#[derive(Serialize)]
+struct Feature<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    geometry: Point,
+    properties: Properties<'a>,
+}
+
+impl<'a> Feature<'a> {
+    fn from_entry(e: &'a Entry) -> Option<Self> {
+        let (latitude, longitude) = e.coordinates()?;
+        Some(Self {
+            kind: "Feature",
+            geometry: Point {
+                kind: "Point",
+                coordinates: [longitude, latitude],
+            },
+            properties: Properties {
+                reference: &e.reference,
+                name: &e.name,
+                program: &e.program,
+                dxcc: &e.dxcc,
+                status: e.status.clone(),
+            },
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Point {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    /// GeoJSON positions are in longitude, latitude order
+    coordinates: [f32; 2],
+}
+
+#[derive(Serialize)]
+struct Properties<'a> {
+    reference: &'a Reference,
+    name: &'a str,
+    program: &'a str,
+    dxcc: &'a str,
+    status: Status,
+}
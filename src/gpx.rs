@@ -0,0 +1,54 @@
+//! Export WWFF directory as GPX waypoints
+
+use std::io::{self, BufWriter, Write};
+
+use tracing::instrument;
+
+use crate::xml::escape;
+use crate::{Entry, WwffDirectory};
+
+const GPX_CREATOR: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+impl WwffDirectory {
+    /// Export entries as [GPX](https://www.topografix.com/gpx.asp) waypoints.
+    ///
+    /// Each entry with coordinates becomes a `<wpt>` with the reference as
+    /// name and the park name as description. Entries without coordinates are
+    /// skipped.
+    pub fn to_gpx<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.to_gpx_filtered(writer, |_| true)
+    }
+
+    /// Export entries matching `filter` as GPX waypoints.
+    ///
+    /// See [WwffDirectory::to_gpx].
+    #[instrument(skip_all)]
+    pub fn to_gpx_filtered<W, F>(&self, writer: W, mut filter: F) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&Entry) -> bool,
+    {
+        let mut w = BufWriter::new(writer);
+
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<gpx version="1.1" creator="{GPX_CREATOR}" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )?;
+
+        for e in self.map.values().filter(|e| filter(e)) {
+            let Some((latitude, longitude)) = e.coordinates() else {
+                continue;
+            };
+            writeln!(
+                w,
+                r#"  <wpt lat="{latitude}" lon="{longitude}"><name>{}</name><desc>{}</desc></wpt>"#,
+                escape(&e.reference),
+                escape(&e.name)
+            )?;
+        }
+
+        writeln!(w, "</gpx>")?;
+        w.flush()
+    }
+}
@@ -0,0 +1,34 @@
+//! Transparent decompression of gzip-compressed CSV files
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// Magic bytes at the start of gzip file
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reader which decompresses its input if it's gzip-compressed
+pub(crate) enum Decoder<R: BufRead> {
+    Plain(R),
+    Gzip(MultiGzDecoder<R>),
+}
+
+impl<R: BufRead> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Decoder::Plain(reader) => reader.read(buf),
+            Decoder::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Detect gzip compression from the magic bytes at the start of input
+pub(crate) fn decode<R: Read>(reader: R) -> io::Result<Decoder<BufReader<R>>> {
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        tracing::debug!("Input is gzip-compressed");
+        Ok(Decoder::Gzip(MultiGzDecoder::new(reader)))
+    } else {
+        Ok(Decoder::Plain(reader))
+    }
+}
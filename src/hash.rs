@@ -0,0 +1,49 @@
+//! Content hash of WWFF directory
+
+use std::io::{self, Write};
+
+use crate::WwffDirectory;
+
+impl WwffDirectory {
+    /// Compute a hash of the directory contents, e.g. to check whether two
+    /// processes have loaded the same version of the directory or to use as
+    /// a cache key.
+    ///
+    /// Entries are hashed in reference order, so the hash doesn't depend on
+    /// the order of rows in the CSV file. Fields are hashed in the format
+    /// written by [WwffDirectory::write_csv] using 64-bit FNV-1a, which
+    /// unlike [std::hash::DefaultHasher] gives the same value on all platforms
+    /// and Rust versions. Values of columns not known by this crate are not
+    /// included.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        // Writing into hasher can't fail
+        let _ = self.write_csv(&mut hasher);
+        hasher.0
+    }
+}
+
+/// 64-bit FNV-1a hash of written bytes
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for b in buf {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(Self::PRIME);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+//! Configuration and state of HTTP downloads shared by async and blocking
+//! downloads
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::builder::ReadOptions;
+use crate::error::ErrorChain;
+use crate::{
+    Entry, Parsed, WwffError, DEFAULT_BACKOFF, DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT,
+    USER_AGENT, WWFF_DIRECTORY_URL,
+};
+
+/// Builder for configuring how WWFF directory is downloaded.
+///
+/// Created with
+/// [WwffDirectory::download_builder](crate::WwffDirectory::download_builder).
+#[derive(Debug, Clone)]
+pub struct DownloadBuilder {
+    url: String,
+    user_agent: String,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    timeout: Option<Duration>,
+    retries: u32,
+    backoff: Duration,
+    proxy: Option<reqwest::Proxy>,
+    accept_invalid_certs: bool,
+    options: ReadOptions,
+}
+
+impl Default for DownloadBuilder {
+    fn default() -> Self {
+        Self {
+            url: WWFF_DIRECTORY_URL.to_string(),
+            user_agent: USER_AGENT.to_string(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            timeout: None,
+            retries: 0,
+            backoff: DEFAULT_BACKOFF,
+            proxy: None,
+            accept_invalid_certs: false,
+            options: ReadOptions::default(),
+        }
+    }
+}
+
+impl DownloadBuilder {
+    /// Download from given URL instead of the original source.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Use given User-Agent header instead of the default
+    /// `wwff-directory/<version>`, e.g. `"MyLogApp/1.2 (+https://example.org)"`.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Timeout for connecting to the server. Default is 30 seconds.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Timeout for waiting for the response headers and for each chunk of the
+    /// response body, so that a server which stalls doesn't hang the download
+    /// forever. Default is 30 seconds.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Timeout for a whole HTTP request including downloading the response
+    /// body. Default is no timeout.
+    ///
+    /// Downloading the whole directory over a slow link can take minutes, so
+    /// leave enough time for it.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Number of times a failed download is retried. Default is no retries.
+    ///
+    /// Only timeouts, connection errors and `5xx` and `429 Too Many Requests`
+    /// responses are retried. Other errors, e.g. `404 Not Found` or an invalid
+    /// CSV file, would fail the same way again.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry. The delay is doubled after each failed
+    /// retry. Default is 1 second.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Route requests through given proxy, e.g.
+    /// `reqwest::Proxy::all("http://proxy.example.org:8080")`.
+    ///
+    /// Without this the proxies from `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY`
+    /// environment variables are used.
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// **DANGER**: Accept any TLS certificate, including self-signed and
+    /// expired ones, and any hostname in it.
+    ///
+    /// This makes HTTPS no more secure than plain HTTP: anyone on the network
+    /// path can tamper with the downloaded directory. Only use for testing
+    /// against a local mirror with self-signed certificate. Requires feature
+    /// "insecure-tls".
+    #[cfg(feature = "insecure-tls")]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Keep only entries for which `predicate` returns true. Other rows are
+    /// dropped while the file is being downloaded.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Entry) -> bool + Send + Sync + 'static,
+    {
+        self.options.set_entry_filter(predicate);
+        self
+    }
+
+    pub(crate) fn build(self) -> Downloader {
+        let mut downloader = Downloader::new();
+        downloader.user_agent = self.user_agent;
+        downloader.connect_timeout = self.connect_timeout;
+        downloader.read_timeout = self.read_timeout;
+        downloader.timeout = self.timeout;
+        downloader.proxy = self.proxy;
+        downloader.accept_invalid_certs = self.accept_invalid_certs;
+        downloader.options = self.options;
+        downloader.url = self.url;
+        downloader.retries = self.retries;
+        downloader.backoff = self.backoff;
+        downloader
+    }
+}
+
+/// Configuration and conditional request state of downloads.
+///
+/// Async and blocking downloads of the same directory use the same instance
+/// so that they share the cache validators.
+#[derive(Debug)]
+pub(crate) struct Downloader {
+    /// HTTP client is created only when first needed so that directories
+    /// read from file don't set up one for nothing.
+    #[cfg(feature = "downloader")]
+    pub(crate) client: Option<reqwest::Client>,
+    /// Blocking client can't be created or dropped inside async runtime, so
+    /// it's created only when first needed.
+    #[cfg(feature = "blocking")]
+    pub(crate) blocking_client: Option<reqwest::blocking::Client>,
+    pub(crate) user_agent: String,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) read_timeout: Duration,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) proxy: Option<reqwest::Proxy>,
+    pub(crate) accept_invalid_certs: bool,
+    pub(crate) options: ReadOptions,
+    pub(crate) url: String,
+    pub(crate) retries: u32,
+    pub(crate) backoff: Duration,
+    /// Where to store a copy of downloaded CSV file
+    pub(crate) cache_path: Option<PathBuf>,
+    last_modified: Option<HeaderValue>,
+    etag: Option<HeaderValue>,
+    /// Modification time of local copy. Sent as `If-Modified-Since` until
+    /// the server reports its own `Last-Modified`.
+    pub(crate) modified_since: Option<HeaderValue>,
+    last_stats: Option<DownloadStats>,
+}
+
+impl Downloader {
+    #[instrument]
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "downloader")]
+            client: None,
+            #[cfg(feature = "blocking")]
+            blocking_client: None,
+            user_agent: USER_AGENT.to_string(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            timeout: None,
+            proxy: None,
+            accept_invalid_certs: false,
+            options: ReadOptions::default(),
+            url: WWFF_DIRECTORY_URL.to_string(),
+            retries: 0,
+            backoff: DEFAULT_BACKOFF,
+            cache_path: None,
+            last_modified: None,
+            etag: None,
+            modified_since: None,
+            last_stats: None,
+        }
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_ref().and_then(|v| v.to_str().ok())
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_ref().and_then(|v| v.to_str().ok())
+    }
+
+    pub fn last_stats(&self) -> Option<DownloadStats> {
+        self.last_stats
+    }
+
+    pub fn cache_state(&self) -> CacheState {
+        let to_string = |v: &HeaderValue| v.to_str().ok().map(str::to_string);
+        CacheState {
+            last_modified: self.last_modified.as_ref().and_then(to_string),
+            etag: self.etag.as_ref().and_then(to_string),
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub fn set_cache_state(&mut self, state: CacheState) {
+        let to_header = |v: String| match HeaderValue::from_str(&v) {
+            Ok(v) => Some(v),
+            Err(err) => {
+                tracing::warn!("Ignoring invalid cache header value {v:?}: {err}");
+                None
+            }
+        };
+        self.last_modified = state.last_modified.and_then(to_header);
+        self.etag = state.etag.and_then(to_header);
+    }
+
+    /// Headers for only downloading the directory if it has changed since
+    /// the last successful download
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(last_modified) = self.last_modified.as_ref().or(self.modified_since.as_ref()) {
+            tracing::debug!("Adding If-Modified-Since header: {last_modified:?}");
+            headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+
+        if let Some(etag) = &self.etag {
+            tracing::debug!("Adding If-None-Match header: {etag:?}");
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+
+        headers
+    }
+
+    /// Check response status. Returns false if the directory wasn't
+    /// modified.
+    pub fn check_status(
+        &mut self,
+        status: StatusCode,
+        url: &reqwest::Url,
+    ) -> Result<bool, WwffError> {
+        // Not modified since last request
+        if status == StatusCode::NOT_MODIFIED {
+            tracing::debug!("{} not modified. Bandwidth saved.", self.url);
+            self.last_stats = Some(DownloadStats {
+                bytes: 0,
+                records: 0,
+                not_modified: true,
+            });
+            return Ok(false);
+        }
+
+        if status != StatusCode::OK {
+            tracing::debug!("Some error {status}");
+            return Err(WwffError::HttpStatus {
+                status,
+                url: url.to_string(),
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Update cache validators from response headers only after the
+    /// directory was successfully downloaded
+    pub fn downloaded(&mut self, headers: &HeaderMap, stats: DownloadStats) {
+        self.last_modified = headers.get(LAST_MODIFIED).cloned();
+        self.etag = headers.get(ETAG).cloned();
+        self.modified_since = None;
+        self.last_stats = Some(stats);
+    }
+
+    /// Start counting retries of a download
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            attempt: 0,
+            retries: self.retries,
+            delay: self.backoff,
+        }
+    }
+}
+
+/// Retries of a single download
+pub(crate) struct Backoff {
+    attempt: u32,
+    retries: u32,
+    delay: Duration,
+}
+
+impl Backoff {
+    /// Get delay before retrying after `err` or [None] if the download
+    /// shouldn't be retried
+    pub fn retry(&mut self, err: &WwffError) -> Option<Duration> {
+        if self.attempt >= self.retries || !err.is_transient() {
+            return None;
+        }
+
+        self.attempt += 1;
+        let delay = self.delay;
+        tracing::warn!(
+            "Download failed: {}. Retry {}/{} in {} ms.",
+            ErrorChain(err),
+            self.attempt,
+            self.retries,
+            delay.as_millis()
+        );
+        self.delay *= 2;
+        Some(delay)
+    }
+}
+
+/// Error for a server which sent nothing within `timeout`
+#[cfg(feature = "downloader")]
+pub(crate) fn read_timed_out(timeout: Duration) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("No response from server in {} ms", timeout.as_millis()),
+    )
+}
+
+/// Read CSV while writing a copy of it to given path.
+///
+/// The copy is first written to a temporary file which replaces the file in
+/// `path` only after the CSV was successfully read.
+pub(crate) fn read_to_cache<R: Read>(
+    reader: R,
+    path: &Path,
+    options: &ReadOptions,
+) -> Result<Parsed, WwffError> {
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+
+    let write_copy = || {
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        let tee = TeeReader {
+            reader,
+            writer: &mut file,
+        };
+        let parsed = crate::read(tee, options)?;
+        file.flush()?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)?;
+        Ok::<_, WwffError>(parsed)
+    };
+
+    match write_copy() {
+        Ok(parsed) => {
+            tracing::debug!("Stored copy of WWFF directory to {}", path.display());
+            Ok(parsed)
+        }
+        Err(err) => {
+            // Don't leave partial copy behind
+            let _ = fs::remove_file(&tmp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Reader which writes everything it reads into a writer
+struct TeeReader<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.writer.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+/// HTTP cache validators from the last successful download.
+///
+/// Store this (e.g. as JSON) and restore it with
+/// [WwffDirectory::set_cache_state](crate::WwffDirectory::set_cache_state) to
+/// make conditional requests across process restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheState {
+    /// Value of `Last-Modified` response header
+    pub last_modified: Option<String>,
+    /// Value of `ETag` response header
+    pub etag: Option<String>,
+}
+
+/// Statistics of the last download
+///
+/// Get with
+/// [WwffDirectory::last_download_stats](crate::WwffDirectory::last_download_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DownloadStats {
+    /// Size of the response body in bytes. With HTTP compression this is the
+    /// size after decompression.
+    pub bytes: u64,
+    /// Number of entries parsed from the response
+    pub records: usize,
+    /// The server responded that the directory wasn't modified
+    pub not_modified: bool,
+}
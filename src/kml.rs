@@ -0,0 +1,42 @@
+//! Export WWFF directory as KML placemarks
+
+use std::io::{self, BufWriter, Write};
+
+use tracing::instrument;
+
+use crate::xml::escape;
+use crate::WwffDirectory;
+
+impl WwffDirectory {
+    /// Export entries as [KML](https://developers.google.com/kml) placemarks
+    /// e.g. for Google Earth.
+    ///
+    /// Each entry with coordinates becomes a `<Placemark>` with the reference
+    /// as name and the park name and status as description. Entries without
+    /// coordinates are skipped.
+    #[instrument(skip_all)]
+    pub fn to_kml<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut w = BufWriter::new(writer);
+
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(w, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+        writeln!(w, "<Document>")?;
+
+        for e in self.map.values() {
+            let Some((latitude, longitude)) = e.coordinates() else {
+                continue;
+            };
+            writeln!(
+                w,
+                "  <Placemark><name>{}</name><description>{} ({})</description><Point><coordinates>{longitude},{latitude}</coordinates></Point></Placemark>",
+                escape(&e.reference),
+                escape(&e.name),
+                escape(e.status.as_str())
+            )?;
+        }
+
+        writeln!(w, "</Document>")?;
+        writeln!(w, "</kml>")?;
+        w.flush()
+    }
+}
@@ -6,18 +6,110 @@
 //!  2. Read WWFF directory from given reader with [from_reader] function.
 //!  3. And lastly the most complex option is to use feature "downloader" which enables functions [WwffDirectory::from_download] and [WwffDirectory::try_download_update].
 //!
+//! If only the reference, name, status, program and coordinates are needed,
+//! [from_path_minimal] and [from_reader_minimal] read a [MinimalDirectory]
+//! which uses less memory.
+//!
+//! With feature "downloader" the CSV file can also be fetched as is without
+//! parsing it with [WwffDownloader].
+//!
+//! Feature "blocking" enables downloading without async runtime with
+//! [WwffDirectory::from_download_blocking] and
+//! [WwffDirectory::try_download_update_blocking]. Conditional requests work
+//! like with async downloads and the state can be saved with
+//! [WwffDirectory::cache_state].
+//!
+//! Feature "async" enables reading WWFF directory from an async reader with
+//! [WwffDirectory::from_async_reader].
+//!
+//! Feature "gzip" enables reading gzip-compressed CSV files. Compression is
+//! detected automatically.
+//!
+//! Feature "bincode" enables saving the parsed directory as a binary snapshot
+//! with [WwffDirectory::save_snapshot] and loading it with
+//! [WwffDirectory::load_snapshot] without parsing CSV again.
+//!
+//! Feature "insecure-tls" together with "downloader" or "blocking" enables
+//! [DownloadBuilder::danger_accept_invalid_certs] for testing against a local
+//! mirror with self-signed certificate. Never enable it in production.
+//!
+//! Feature "extra-columns" keeps values of columns not known by this crate in
+//! [Entry::extra].
+//!
+//! Feature "geojson" enables exporting the directory as GeoJSON with
+//! [WwffDirectory::to_geojson].
+//!
 //! The official CSV file can be found from <https://wwff.co/wwff-data/wwff_directory.csv>.
 
-use std::collections::BTreeMap;
-use std::io::{self, Read};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Read, Write};
+#[cfg(feature = "downloader")]
+use std::ops::ControlFlow;
 use std::path::Path;
 
-use serde::{Deserialize, Deserializer};
-use tinystr::TinyAsciiStr;
+use serde::{Deserialize, Deserializer, Serialize};
 use tracing::instrument;
 
+use builder::ReadOptions;
+#[cfg(feature = "downloader")]
+use error::ErrorChain;
+#[cfg(feature = "gzip")]
+use gzip::decode;
+
+#[cfg(feature = "blocking")]
+mod blocking;
+mod builder;
+mod changelog;
+mod diff;
 #[cfg(feature = "downloader")]
 mod downloader;
+mod error;
+mod geo;
+#[cfg(feature = "geojson")]
+mod geojson;
+mod gpx;
+#[cfg(feature = "gzip")]
+mod gzip;
+mod hash;
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+mod http;
+mod kml;
+mod lookup;
+mod maidenhead;
+mod merge;
+mod minimal;
+mod mojibake;
+mod program;
+mod report;
+#[cfg(feature = "bincode")]
+mod snapshot;
+mod sort;
+mod stats;
+mod suggest;
+mod validate;
+mod xml;
+
+pub use builder::DirectoryBuilder;
+pub use changelog::ChangeLogEntry;
+pub use diff::DirectoryDiff;
+#[cfg(feature = "downloader")]
+pub use downloader::WwffDownloader;
+pub use error::WwffError;
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+pub use http::{CacheState, DownloadBuilder, DownloadStats};
+pub use lookup::LookupDirectory;
+pub use maidenhead::{coordinates_to_maidenhead, maidenhead_to_coordinates};
+pub use merge::MergeStrategy;
+pub use minimal::{from_path_minimal, from_reader_minimal, MinimalDirectory, MinimalEntry};
+pub use program::Program;
+pub use report::{DuplicateRow, InvalidValue, ParseReport, ParseWarning, SkippedRow};
+pub use sort::{sort_entries_by, SortKey};
+pub use stats::DirectoryStats;
+pub use validate::ValidationIssue;
+
+// Types of external crates used in public API
+pub use chrono::NaiveDate;
+pub use tinystr::TinyAsciiStr;
 
 /// WWFF Unique Identifying Reference number
 ///
@@ -36,35 +128,169 @@ pub type Reference = TinyAsciiStr<12>;
 
 type WwffMap = BTreeMap<Reference, Entry>;
 
+/// Official source of WWFF directory
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+const WWFF_DIRECTORY_URL: &str = "https://wwff.co/wwff-data/wwff_directory.csv";
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+const DEFAULT_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+#[cfg(any(feature = "downloader", feature = "blocking"))]
+const DEFAULT_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Result of reading CSV file
+#[derive(Debug)]
+struct Parsed<T = Entry> {
+    map: BTreeMap<Reference, T>,
+    report: ParseReport,
+    /// Line numbers of rows in `map` for reporting duplicates
+    lines: BTreeMap<Reference, Option<u64>>,
+}
+
+impl<T: Row> Parsed<T> {
+    /// Add parsed row into map or skipped row into report
+    fn push(&mut self, row: RowResult<T>, options: &ReadOptions) {
+        match row {
+            Ok((line, e, warnings)) => {
+                for warning in warnings {
+                    options.warn(warning);
+                }
+                let reference = e.reference().to_ascii_uppercase();
+                if let Some(first_line) = self.lines.insert(reference, line) {
+                    let duplicate = DuplicateRow {
+                        reference: reference.to_string(),
+                        first_line,
+                        line,
+                    };
+                    options.warn(ParseWarning::Duplicate(duplicate.clone()));
+                    self.report.duplicates.push(duplicate);
+                }
+                self.map.insert(reference, e);
+            }
+            Err(skipped) => {
+                options.warn(ParseWarning::SkippedRow(skipped.clone()));
+                self.report.skipped_rows.push(skipped);
+            }
+        }
+    }
+}
+
+/// Deserialized row with its line number and warnings about its fields, or
+/// skipped row
+type RowResult<T> = Result<(Option<u64>, T, Vec<ParseWarning>), SkippedRow>;
+
+/// A row of WWFF directory CSV file
+trait Row: serde::de::DeserializeOwned + Send {
+    /// Columns which must be present in CSV file
+    const REQUIRED_COLUMNS: &'static [&'static str];
+
+    fn reference(&self) -> Reference;
+
+    /// Trim and collapse whitespace in free text fields
+    fn normalize_text(&mut self) {}
+
+    /// Check if row should be kept after it was parsed
+    fn keep(&self, _options: &ReadOptions) -> bool {
+        true
+    }
+
+    /// Store values of columns not known by this crate
+    #[cfg(feature = "extra-columns")]
+    fn set_extra(&mut self, _extra: BTreeMap<String, String>) {}
+}
+
+impl Row for Entry {
+    const REQUIRED_COLUMNS: &'static [&'static str] = &[
+        "reference",
+        "status",
+        "name",
+        "program",
+        "dxcc",
+        "state",
+        "county",
+        "continent",
+        "reviewFlag",
+    ];
+
+    fn reference(&self) -> Reference {
+        self.reference
+    }
+
+    fn normalize_text(&mut self) {
+        self.name = normalize_text(&self.name);
+        self.notes = normalize_text(&self.notes);
+    }
+
+    fn keep(&self, options: &ReadOptions) -> bool {
+        options.keeps_entry(self)
+    }
+
+    #[cfg(feature = "extra-columns")]
+    fn set_extra(&mut self, extra: BTreeMap<String, String>) {
+        self.extra = extra;
+    }
+}
+
 /// The directory containing WWFF information
 #[derive(Debug)]
 pub struct WwffDirectory {
     map: WwffMap,
-    #[cfg(feature = "downloader")]
-    downloader: downloader::Downloader,
+    report: ParseReport,
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    downloader: http::Downloader,
 }
 
 impl WwffDirectory {
+    fn from_parsed(parsed: Parsed) -> Self {
+        Self {
+            map: parsed.map,
+            report: parsed.report,
+            #[cfg(any(feature = "downloader", feature = "blocking"))]
+            downloader: http::Downloader::new(),
+        }
+    }
+
+    /// Configure how WWFF directory is read, e.g. to skip parsing some of the
+    /// fields.
+    pub fn builder() -> DirectoryBuilder {
+        DirectoryBuilder::default()
+    }
+
     /// Read CSV file from given [Path]
     #[instrument(fields(path = %path.as_ref().to_string_lossy()))]
-    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<WwffDirectory> {
-        let map = read(csv::Reader::from_path(path)?)?;
-        Ok(Self {
-            map,
-            #[cfg(feature = "downloader")]
-            downloader: downloader::Downloader::new(),
-        })
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<WwffDirectory, WwffError> {
+        Self::builder().from_path(path)
     }
 
     /// Read CSV file from given reader
     #[instrument(skip(reader))]
-    pub fn from_reader<R: Read>(reader: R) -> io::Result<WwffDirectory> {
-        let map = read(csv::Reader::from_reader(reader))?;
-        Ok(Self {
-            map,
-            #[cfg(feature = "downloader")]
-            downloader: downloader::Downloader::new(),
-        })
+    pub fn from_reader<R: Read>(reader: R) -> Result<WwffDirectory, WwffError> {
+        Self::builder().from_reader(reader)
+    }
+
+    /// Read CSV file from given bytes
+    ///
+    /// CSV in a string can be parsed with [str::parse] as [WwffDirectory]
+    /// implements [FromStr](std::str::FromStr).
+    pub fn from_bytes(bytes: &[u8]) -> Result<WwffDirectory, WwffError> {
+        Self::from_reader(bytes)
+    }
+
+    /// Read CSV file from given async reader
+    ///
+    /// The CSV is parsed on a blocking thread so the async executor isn't
+    /// blocked while the directory is read.
+    #[cfg(feature = "async")]
+    #[instrument(skip(reader))]
+    pub async fn from_async_reader<R>(reader: R) -> Result<WwffDirectory, WwffError>
+    where
+        R: tokio::io::AsyncRead + Send + Unpin + 'static,
+    {
+        Ok(Self::from_parsed(
+            read_async(reader, ReadOptions::default()).await?,
+        ))
     }
 
     /// Download WWFF directory from it's original source.
@@ -73,167 +299,1096 @@ impl WwffDirectory {
     /// in-place with [WwffDirectory::try_download_update] function.
     #[cfg(feature = "downloader")]
     #[instrument]
-    pub async fn from_download() -> io::Result<WwffDirectory> {
-        let mut downloader = downloader::Downloader::new();
-        let map = downloader.download().await?;
-        match map {
-            Some(map) => Ok(Self { map, downloader }),
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "initial download failed",
-            )),
+    pub async fn from_download() -> Result<WwffDirectory, WwffError> {
+        Self::from_download_with(http::Downloader::new()).await
+    }
+
+    /// Download WWFF directory from given URL instead of the original source.
+    ///
+    /// This is useful for using a mirror or a local HTTP server. Updates with
+    /// [WwffDirectory::try_download_update] are downloaded from the same URL.
+    #[cfg(feature = "downloader")]
+    #[instrument]
+    pub async fn from_download_url(url: &str) -> Result<WwffDirectory, WwffError> {
+        Self::from_download_with(http::Downloader::with_url(url)).await
+    }
+
+    /// Read WWFF directory from a response body fetched outside of this crate,
+    /// e.g. with own HTTP client.
+    ///
+    /// `etag` and `last_modified` are the `ETag` and `Last-Modified` headers
+    /// of the response. They are used by [WwffDirectory::try_download_update]
+    /// to only download the directory if it has changed since.
+    #[cfg(feature = "downloader")]
+    #[instrument(skip(body))]
+    pub fn from_response_parts(
+        body: &[u8],
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> Result<WwffDirectory, WwffError> {
+        let mut directory = Self::from_bytes(body)?;
+        directory.set_cache_state(CacheState {
+            last_modified: last_modified.map(str::to_string),
+            etag: etag.map(str::to_string),
+        });
+        Ok(directory)
+    }
+
+    /// Read WWFF directory from a local copy in given [Path] or download it if
+    /// there's no local copy.
+    ///
+    /// A fresh copy is written to the path after each successful download,
+    /// including the ones done with [WwffDirectory::try_download_update]. When
+    /// the local copy is used, the next update is only downloaded if the
+    /// directory has been modified after the local copy was written.
+    #[cfg(feature = "downloader")]
+    #[instrument(fields(path = %path.as_ref().to_string_lossy()))]
+    pub async fn from_cache_or_download<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<WwffDirectory, WwffError> {
+        Self::from_cache_or_download_with(http::Downloader::new(), path.as_ref()).await
+    }
+
+    #[cfg(feature = "downloader")]
+    async fn from_cache_or_download_with(
+        mut downloader: http::Downloader,
+        path: &Path,
+    ) -> Result<WwffDirectory, WwffError> {
+        let path = path.to_path_buf();
+        downloader.set_cache_path(&path);
+
+        let cached = {
+            let path = path.clone();
+            let options = downloader.read_options().clone();
+            tokio::task::spawn_blocking(move || {
+                let modified = std::fs::metadata(&path)?.modified()?;
+                let parsed = read(std::fs::File::open(&path)?, &options)?;
+                Ok::<_, WwffError>((Self::from_parsed(parsed), modified))
+            })
+            .await
+            .map_err(io::Error::other)?
+        };
+
+        match cached {
+            Ok((directory, modified)) => {
+                downloader.set_modified_since(modified);
+                Ok(Self {
+                    downloader,
+                    ..directory
+                })
+            }
+            Err(err) => {
+                tracing::info!(
+                    "Couldn't read local copy of WWFF directory: {}",
+                    ErrorChain(&err)
+                );
+                Self::from_download_with(downloader).await
+            }
+        }
+    }
+
+    /// Download WWFF directory keeping only entries for which `predicate`
+    /// returns true.
+    ///
+    /// Rows are parsed and filtered while the file is being downloaded, so
+    /// memory use is proportional to the kept entries instead of the whole
+    /// directory. Updates with [WwffDirectory::try_download_update] use the
+    /// same predicate. See also [DownloadBuilder::filter].
+    #[cfg(feature = "downloader")]
+    #[instrument(skip(predicate))]
+    pub async fn from_download_filtered<F>(predicate: F) -> Result<WwffDirectory, WwffError>
+    where
+        F: Fn(&Entry) -> bool + Send + Sync + 'static,
+    {
+        Self::download_builder().filter(predicate).download().await
+    }
+
+    /// Configure how WWFF directory is downloaded, e.g. timeouts and
+    /// retries.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn download_builder() -> DownloadBuilder {
+        DownloadBuilder::default()
+    }
+
+    #[cfg(feature = "downloader")]
+    async fn from_download_with(
+        mut downloader: http::Downloader,
+    ) -> Result<WwffDirectory, WwffError> {
+        let parsed = downloader.download().await?;
+        match parsed {
+            Some(parsed) => Ok(Self {
+                downloader,
+                ..Self::from_parsed(parsed)
+            }),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "initial download failed").into()),
         }
     }
 
     /// Try to download updated version of WWFF directory. If there's new
     /// version available then the directory is updated automatically.
+    ///
+    /// Returns the differences to the previous version if the directory was
+    /// updated or [None] if it wasn't modified.
+    #[cfg(feature = "downloader")]
+    #[instrument(skip(self))]
+    pub async fn try_download_update(&mut self) -> Result<Option<DirectoryDiff>, WwffError> {
+        let Some(parsed) = self.downloader.download().await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.apply_update(parsed)))
+    }
+
+    /// Periodically try to download updated version of WWFF directory.
+    ///
+    /// `on_update` is called with the updated directory each time a new
+    /// version was downloaded. Polls where the directory wasn't modified don't
+    /// call `on_update`. Failed downloads are logged and retried on the next
+    /// poll.
+    ///
+    /// Watching continues until `on_update` returns [ControlFlow::Break] and
+    /// its value is returned.
     #[cfg(feature = "downloader")]
+    #[instrument(skip(self, on_update))]
+    pub async fn watch<B, F>(&mut self, interval: std::time::Duration, mut on_update: F) -> B
+    where
+        F: FnMut(&WwffDirectory) -> ControlFlow<B>,
+    {
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.try_download_update().await {
+                Ok(Some(_)) => {
+                    if let ControlFlow::Break(b) = on_update(self) {
+                        return b;
+                    }
+                }
+                Ok(None) => (),
+                Err(err) => {
+                    tracing::warn!("Checking for update failed: {}", ErrorChain(&err))
+                }
+            }
+        }
+    }
+
+    /// Replace entries with updated version and return the differences
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    fn apply_update(&mut self, parsed: Parsed) -> DirectoryDiff {
+        let diff = diff::diff(&self.map, &parsed.map);
+        self.map = parsed.map;
+        self.report = parsed.report;
+        diff
+    }
+
+    /// Download WWFF directory from it's original source without async
+    /// runtime.
+    ///
+    /// After this initial download it's possible to update the WWFF directory
+    /// in-place with [WwffDirectory::try_download_update_blocking] function.
+    #[cfg(feature = "blocking")]
+    #[instrument]
+    pub fn from_download_blocking() -> Result<WwffDirectory, WwffError> {
+        Self::from_download_blocking_with(http::Downloader::new())
+    }
+
+    #[cfg(feature = "blocking")]
+    fn from_download_blocking_with(
+        mut downloader: http::Downloader,
+    ) -> Result<WwffDirectory, WwffError> {
+        match downloader.download_blocking()? {
+            Some(parsed) => Ok(Self {
+                downloader,
+                ..Self::from_parsed(parsed)
+            }),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "initial download failed").into()),
+        }
+    }
+
+    /// Try to download updated version of WWFF directory without async
+    /// runtime. If there's new version available then the directory is
+    /// updated automatically.
+    ///
+    /// Returns the differences to the previous version like
+    /// [WwffDirectory::try_download_update].
+    #[cfg(feature = "blocking")]
     #[instrument(skip(self))]
-    pub async fn try_download_update(&mut self) -> io::Result<()> {
-        if let Some(map) = self.downloader.download().await? {
-            self.map = map;
+    pub fn try_download_update_blocking(&mut self) -> Result<Option<DirectoryDiff>, WwffError> {
+        let Some(parsed) = self.downloader.download_blocking()? else {
+            return Ok(None);
+        };
+        Ok(Some(self.apply_update(parsed)))
+    }
+
+    /// Get the `Last-Modified` date reported by the server on the last
+    /// successful download, e.g. `"Wed, 21 Oct 2015 07:28:00 GMT"`.
+    ///
+    /// Returns [None] if the directory wasn't downloaded or the server didn't
+    /// report the date.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn last_modified(&self) -> Option<&str> {
+        self.downloader.last_modified()
+    }
+
+    /// Get the `ETag` reported by the server on the last successful download.
+    ///
+    /// Returns [None] if the directory wasn't downloaded or the server didn't
+    /// report an `ETag`.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn etag(&self) -> Option<&str> {
+        self.downloader.etag()
+    }
+
+    /// Get statistics of the last successful download or update check.
+    ///
+    /// Returns [None] if the directory wasn't downloaded.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn last_download_stats(&self) -> Option<DownloadStats> {
+        self.downloader.last_stats()
+    }
+
+    /// Get HTTP cache validators of the last successful download.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn cache_state(&self) -> CacheState {
+        self.downloader.cache_state()
+    }
+
+    /// Restore HTTP cache validators previously saved with
+    /// [WwffDirectory::cache_state].
+    ///
+    /// This can be used with a WWFF directory read from file so that the next
+    /// update, async or blocking, only downloads the directory if it has
+    /// changed since.
+    #[cfg(any(feature = "downloader", feature = "blocking"))]
+    pub fn set_cache_state(&mut self, state: CacheState) {
+        self.downloader.set_cache_state(state);
+    }
+
+    /// Write WWFF directory as CSV to given writer.
+    ///
+    /// The output uses the same columns as the official CSV file and can be
+    /// read back with [WwffDirectory::from_reader].
+    #[instrument(skip_all)]
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        // Header is written also when there are no entries so that the output
+        // can be read back
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        wtr.write_record(HEADERS)?;
+        for entry in self.map.values() {
+            wtr.serialize(entry)?;
         }
-        Ok(())
+        wtr.flush()
+    }
+
+    /// Get report of problems found while reading the directory.
+    pub fn parse_report(&self) -> &ParseReport {
+        &self.report
     }
 
     /// Search WWFF directory for reference.
+    ///
+    /// The reference is normalized with [normalize_reference] before lookup.
+    /// Deleted entries are returned as well, see [Entry::is_active].
     #[instrument]
     pub fn search_reference(&self, s: &str) -> Option<&Entry> {
-        let reference = TinyAsciiStr::from_str(s).ok()?.to_ascii_uppercase();
+        let reference = normalize_reference(s)?;
         self.map.get(&reference)
     }
+
+    /// Get entry with given [Reference].
+    ///
+    /// Unlike [WwffDirectory::search_reference] the reference isn't parsed
+    /// or normalized, only matched case-insensitively.
+    pub fn get(&self, reference: &Reference) -> Option<&Entry> {
+        self.map.get(&reference.to_ascii_uppercase())
+    }
+
+    /// Keep only entries for which `f` returns true, e.g. to drop deleted
+    /// entries after reading.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Reference, &Entry) -> bool,
+    {
+        self.map.retain(|reference, entry| f(reference, entry));
+    }
+
+    /// Get all entries in reference order.
+    ///
+    /// The entries are already stored in reference order, so this only walks
+    /// the directory without sorting.
+    pub fn sorted_entries(&self) -> Vec<&Entry> {
+        self.map.values().collect()
+    }
+
+    /// Get at most `limit` entries in reference order starting from
+    /// `offset`, e.g. for paginated list views.
+    ///
+    /// Like [WwffDirectory::sorted_entries] this doesn't sort, but entries
+    /// before `offset` are still walked through.
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<&Entry> {
+        self.map.values().skip(offset).take(limit).collect()
+    }
+
+    /// Convert the directory into [LookupDirectory] for faster lookups by
+    /// reference when ordered iteration and prefix queries are not needed.
+    pub fn into_lookup(self) -> LookupDirectory {
+        LookupDirectory::new(self.map.into_iter().collect(), self.report)
+    }
+
+    /// Consume the directory into its entries in reference order.
+    pub fn into_entries(self) -> impl Iterator<Item = (Reference, Entry)> {
+        self.map.into_iter()
+    }
+
+    /// Check if WWFF directory contains given reference.
+    ///
+    /// The reference is normalized with [normalize_reference] before lookup.
+    pub fn contains(&self, reference: &str) -> bool {
+        normalize_reference(reference).is_some_and(|r| self.map.contains_key(&r))
+    }
+
+    /// Search WWFF directory for many references at once.
+    ///
+    /// Each reference is looked up like with
+    /// [WwffDirectory::search_reference]. Result contains the given reference
+    /// together with the matching entry, if any, in the order of input.
+    pub fn search_references<'a>(
+        &self,
+        refs: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<(String, Option<&Entry>)> {
+        refs.into_iter()
+            .map(|s| (s.to_string(), self.search_reference(s)))
+            .collect()
+    }
+
+    /// Iterate over entries whose reference starts with `prefix`, e.g.
+    /// `"ONFF-"`.
+    ///
+    /// Matching is case-insensitive. This is a range scan over the sorted
+    /// directory so it doesn't need to go through all entries.
+    pub fn entries_with_prefix(&self, prefix: &str) -> impl Iterator<Item = &Entry> {
+        let start = Reference::from_str(prefix)
+            .ok()
+            .map(|s| s.to_ascii_uppercase());
+        start.into_iter().flat_map(move |start| {
+            self.map
+                .range(start..)
+                .take_while(move |(reference, _)| reference.starts_with(start.as_str()))
+                .map(|(_, entry)| entry)
+        })
+    }
+
+    /// Search WWFF directory for entries whose name contains `query`.
+    ///
+    /// Matching is case-insensitive. Results are sorted by reference and at
+    /// most `limit` entries are returned if given. Note that this does a linear
+    /// scan over the whole directory.
+    #[instrument(skip(self))]
+    pub fn search_name(&self, query: &str, limit: Option<usize>) -> Vec<&Entry> {
+        let query = query.to_lowercase();
+        self.map
+            .values()
+            .filter(|e| e.name.to_lowercase().contains(&query))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Iterate over entries valid on given date.
+    ///
+    /// See [Entry::is_valid_on] for how missing validity dates are treated.
+    pub fn entries_valid_on(&self, date: chrono::NaiveDate) -> impl Iterator<Item = &Entry> {
+        self.map.values().filter(move |e| e.is_valid_on(date))
+    }
+
+    /// Iterate over entries valid today.
+    ///
+    /// Today is the current date in UTC. Use
+    /// [WwffDirectory::entries_valid_on] with explicit date for other time
+    /// zones.
+    pub fn currently_valid(&self) -> impl Iterator<Item = &Entry> {
+        self.entries_valid_on(chrono::Utc::now().date_naive())
+    }
+
+    /// Iterate over entries activated on or after given date.
+    ///
+    /// Entries without recorded activity are skipped.
+    pub fn activated_since(&self, date: chrono::NaiveDate) -> impl Iterator<Item = &Entry> {
+        self.map
+            .values()
+            .filter(move |e| e.last_activity.is_some_and(|d| d >= date))
+    }
+
+    /// Get all distinct IOTA references found in the directory.
+    pub fn iota_references(&self) -> BTreeSet<TinyAsciiStr<8>> {
+        self.map.values().filter_map(|e| e.iota).collect()
+    }
+
+    /// Iterate over entries on given IOTA reference, e.g. `"EU-001"`.
+    ///
+    /// Matching is case-insensitive. Entries without IOTA reference are
+    /// skipped.
+    pub fn entries_by_iota<'a>(&'a self, iota: &'a str) -> impl Iterator<Item = &'a Entry> {
+        self.map
+            .values()
+            .filter(move |e| e.iota.is_some_and(|i| i.eq_ignore_ascii_case(iota.trim())))
+    }
+
+    /// Get all distinct IUCN categories found in the directory.
+    pub fn iucn_categories(&self) -> BTreeSet<TinyAsciiStr<12>> {
+        self.map.values().filter_map(|e| e.iucn_category).collect()
+    }
+
+    /// Iterate over entries with given IUCN category.
+    ///
+    /// Matching is case-insensitive. Entries without IUCN category are
+    /// skipped.
+    pub fn entries_by_iucn<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a Entry> {
+        self.map.values().filter(move |e| {
+            e.iucn_category
+                .is_some_and(|c| c.eq_ignore_ascii_case(category.trim()))
+        })
+    }
+
+    /// Get all distinct country names found in the directory.
+    pub fn countries(&self) -> BTreeSet<String> {
+        self.map
+            .values()
+            .filter_map(|e| e.country.clone())
+            .collect()
+    }
+
+    /// Iterate over entries with given country name.
+    ///
+    /// Matching is case-insensitive. Entries without country are skipped.
+    pub fn entries_by_country(&self, country: &str) -> impl Iterator<Item = &Entry> {
+        let country = country.trim().to_lowercase();
+        self.map.values().filter(move |e| {
+            e.country
+                .as_ref()
+                .is_some_and(|c| c.to_lowercase() == country)
+        })
+    }
+
+    /// Iterate over entries flagged for review, i.e. with non-zero
+    /// `review_flag`.
+    pub fn entries_flagged_for_review(&self) -> impl Iterator<Item = &Entry> {
+        self.map.values().filter(|e| e.review_flag != 0)
+    }
+
+    /// Iterate over entries modified on or after given date.
+    ///
+    /// Entries without `last_modified` date are skipped.
+    pub fn modified_since(&self, date: chrono::NaiveDate) -> impl Iterator<Item = &Entry> {
+        self.map
+            .values()
+            .filter(move |e| e.last_modified.is_some_and(|modified| modified >= date))
+    }
+
+    /// Iterate over entries with `special_flags`.
+    pub fn entries_with_special_flags(&self) -> impl Iterator<Item = &Entry> {
+        self.map.values().filter(|e| e.special_flags.is_some())
+    }
+
+    /// Iterate over entries with given [Status].
+    pub fn entries_by_status(&self, status: Status) -> impl Iterator<Item = &Entry> {
+        self.map.values().filter(move |e| e.status == status)
+    }
+
+    /// Iterate over deleted entries, i.e. with [Status::Deleted].
+    ///
+    /// Deleted references are still found by
+    /// [WwffDirectory::search_reference]. Use [Entry::is_active] to check
+    /// whether an entry still counts for awards.
+    pub fn deleted_references(&self) -> impl Iterator<Item = &Entry> {
+        self.entries_by_status(Status::Deleted)
+    }
 }
 
 /// A single WWFF entity entry
-#[derive(Debug, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Entry {
     pub reference: Reference,
     #[serde(deserialize_with = "deserialize_status")]
     pub status: Status,
+    #[serde(deserialize_with = "deserialize_string")]
     pub name: String,
     pub program: TinyAsciiStr<12>,
     pub dxcc: TinyAsciiStr<8>,
     pub state: TinyAsciiStr<8>,
     pub county: TinyAsciiStr<8>,
     pub continent: TinyAsciiStr<2>,
-    #[serde(deserialize_with = "deserialize_tinystr")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_tinystr",
+        serialize_with = "serialize_tinystr"
+    )]
     pub iota: Option<TinyAsciiStr<8>>,
     #[serde(
-        rename(deserialize = "iaruLocator"),
-        deserialize_with = "deserialize_tinystr"
+        default,
+        rename = "iaruLocator",
+        deserialize_with = "deserialize_tinystr",
+        serialize_with = "serialize_tinystr"
     )]
     pub iaru_locator: Option<TinyAsciiStr<12>>,
-    #[serde(deserialize_with = "deserialize_f32_opt")]
+    #[serde(default, deserialize_with = "deserialize_f32_opt")]
     pub latitude: Option<f32>,
-    #[serde(deserialize_with = "deserialize_f32_opt")]
+    #[serde(default, deserialize_with = "deserialize_f32_opt")]
     pub longitude: Option<f32>,
     /// International Union for Conservation of Nature (IUCN) category
     #[serde(
-        rename(deserialize = "IUCNcat"),
-        deserialize_with = "deserialize_tinystr"
+        default,
+        rename = "IUCNcat",
+        deserialize_with = "deserialize_tinystr",
+        serialize_with = "serialize_tinystr"
     )]
     pub iucn_category: Option<TinyAsciiStr<12>>,
     #[serde(
-        rename(deserialize = "validFrom"),
+        default,
+        rename = "validFrom",
         deserialize_with = "deserialize_date_opt"
     )]
     pub valid_from: Option<chrono::NaiveDate>,
-    #[serde(
-        rename(deserialize = "validTo"),
-        deserialize_with = "deserialize_date_opt"
-    )]
+    #[serde(default, rename = "validTo", deserialize_with = "deserialize_date_opt")]
     pub valid_to: Option<chrono::NaiveDate>,
+    #[serde(default, deserialize_with = "deserialize_string")]
     pub notes: String,
-    #[serde(rename(deserialize = "lastMod"))]
-    pub last_modified: String,
+    #[serde(default, rename = "lastMod", deserialize_with = "deserialize_date_opt")]
+    pub last_modified: Option<chrono::NaiveDate>,
     #[serde(
-        rename(deserialize = "changeLog"),
+        default,
+        rename = "changeLog",
         deserialize_with = "deserialize_string_opt"
     )]
     pub changelog: Option<String>,
-    #[serde(rename(deserialize = "reviewFlag"))]
+    #[serde(rename = "reviewFlag")]
     pub review_flag: u8,
     #[serde(
-        rename(deserialize = "specialFlags"),
+        default,
+        rename = "specialFlags",
         deserialize_with = "deserialize_string_opt"
     )]
     pub special_flags: Option<String>,
-    #[serde(deserialize_with = "deserialize_string_opt")]
+    #[serde(default, deserialize_with = "deserialize_string_opt")]
     pub website: Option<String>,
-    #[serde(deserialize_with = "deserialize_string_opt")]
+    #[serde(default, deserialize_with = "deserialize_string_opt")]
     pub country: Option<String>,
-    #[serde(deserialize_with = "deserialize_string_opt")]
+    #[serde(default, deserialize_with = "deserialize_string_opt")]
     pub region: Option<String>,
-    #[serde(rename(deserialize = "dxccEnum"))]
+    #[serde(rename = "dxccEnum")]
     pub dxcc_enum: Option<u16>,
-    #[serde(rename(deserialize = "qsoCount"))]
+    #[serde(rename = "qsoCount")]
     pub qso_count: Option<u32>,
-    #[serde(
-        rename(deserialize = "lastAct"),
-        deserialize_with = "deserialize_date_opt"
-    )]
+    #[serde(default, rename = "lastAct", deserialize_with = "deserialize_date_opt")]
     pub last_activity: Option<chrono::NaiveDate>,
+    /// Columns not known by this crate by their header. Requires feature
+    /// "extra-columns".
+    ///
+    /// These are not written by [WwffDirectory::write_csv] or saved in
+    /// snapshots.
+    #[cfg(feature = "extra-columns")]
+    #[serde(skip)]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Entry {
+    /// Parse entry from a single CSV record, e.g. to read the directory
+    /// incrementally into own storage.
+    ///
+    /// Columns must be in the order of the official WWFF directory CSV file.
+    /// Files with other column order can be read with
+    /// [WwffDirectory::from_reader]. `name` and `notes` are normalized like
+    /// by default when reading the whole directory, see
+    /// [DirectoryBuilder::raw_text].
+    pub fn from_csv_record(record: &csv::StringRecord) -> Result<Entry, WwffError> {
+        static HEADER_RECORD: std::sync::OnceLock<csv::StringRecord> = std::sync::OnceLock::new();
+        let headers = HEADER_RECORD.get_or_init(|| csv::StringRecord::from(HEADERS));
+        let mut entry: Entry = record.deserialize(Some(headers))?;
+        entry.normalize_text();
+        Ok(entry)
+    }
+
+    /// Coordinates of the entry as latitude and longitude.
+    ///
+    /// Explicit coordinates are used if present. Otherwise approximate
+    /// coordinates are derived from the center of the `iaru_locator` grid
+    /// square. Geographic queries like [WwffDirectory::nearest] and the
+    /// GeoJSON, GPX and KML exports use these coordinates.
+    pub fn coordinates(&self) -> Option<(f32, f32)> {
+        match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => Some((latitude, longitude)),
+            _ => maidenhead_to_coordinates(self.iaru_locator.as_ref()?),
+        }
+    }
+
+    /// Check if the entry has [Status::Active].
+    pub fn is_active(&self) -> bool {
+        self.status == Status::Active
+    }
+
+    /// Check if the entry is valid on given date.
+    ///
+    /// Both `valid_from` and `valid_to` are inclusive. Missing dates are
+    /// treated as unbounded, so an entry without either date is always valid.
+    pub fn is_valid_on(&self, date: chrono::NaiveDate) -> bool {
+        self.valid_from.is_none_or(|from| from <= date) && self.valid_to.is_none_or(|to| date <= to)
+    }
+
+    /// Compute Maidenhead grid locator from the coordinates of the entry.
+    ///
+    /// `precision` is the number of characters in the locator, e.g. 4 for
+    /// `"KP20"` or 6 for `"KP20le"`. Returns [None] if the entry has no
+    /// coordinates or precision is not 2, 4, 6 or 8.
+    pub fn maidenhead(&self, precision: usize) -> Option<TinyAsciiStr<12>> {
+        coordinates_to_maidenhead(self.latitude?, self.longitude?, precision)
+    }
 }
 
 /// Status of the [Entry]
-#[derive(Debug)]
+///
+/// Serialized as lowercase string (e.g. `"active"`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Status {
     Active,
     Deleted,
     National,
     Proposed,
+    /// Status not known by this crate. Contains the original value.
+    Other(String),
+}
+
+impl Status {
+    /// Status as a lowercase string, e.g. `"active"`.
+    ///
+    /// For [Status::Other] the original value is returned as is.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Status::Active => "active",
+            Status::Deleted => "deleted",
+            Status::National => "national",
+            Status::Proposed => "proposed",
+            Status::Other(s) => s.as_str(),
+        }
+    }
+}
+
+impl Serialize for Status {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for WwffDirectory {
+    type Err = WwffError;
+
+    /// Read CSV file from given string
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+impl FromIterator<Entry> for WwffDirectory {
+    /// Create directory from entries. Later entries with the same reference
+    /// replace earlier ones.
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        let mut directory = Self::from_parsed(Parsed {
+            map: BTreeMap::new(),
+            report: ParseReport::default(),
+            lines: BTreeMap::new(),
+        });
+        directory.extend(iter);
+        directory
+    }
+}
+
+impl Extend<Entry> for WwffDirectory {
+    /// Add entries to directory. Entries with a reference already in the
+    /// directory replace the existing ones.
+    fn extend<I: IntoIterator<Item = Entry>>(&mut self, iter: I) {
+        self.map.extend(
+            iter.into_iter()
+                .map(|e| (e.reference.to_ascii_uppercase(), e)),
+        );
+    }
 }
 
 /// Read CSV file from given [Path]
-pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<WwffDirectory> {
+pub fn from_path<P: AsRef<Path>>(path: P) -> Result<WwffDirectory, WwffError> {
     WwffDirectory::from_path(path)
 }
 
 /// Read CSV file from given reader
-pub fn from_reader<R: Read>(reader: R) -> io::Result<WwffDirectory> {
+pub fn from_reader<R: Read>(reader: R) -> Result<WwffDirectory, WwffError> {
     WwffDirectory::from_reader(reader)
 }
 
-#[instrument(skip(rdr))]
-fn read<R: Read>(mut rdr: csv::Reader<R>) -> io::Result<WwffMap> {
-    let mut map = BTreeMap::new();
-    let ts = std::time::Instant::now();
+/// Read CSV file from given async reader
+#[cfg(feature = "async")]
+pub async fn from_async_reader<R>(reader: R) -> Result<WwffDirectory, WwffError>
+where
+    R: tokio::io::AsyncRead + Send + Unpin + 'static,
+{
+    WwffDirectory::from_async_reader(reader).await
+}
 
-    for entry in rdr.deserialize() {
-        match entry {
-            Ok(e) => {
-                let e: Entry = e;
-                let reference = e.reference.to_ascii_uppercase();
-                map.insert(reference, e);
-            }
-            Err(err) => {
-                tracing::error!("Skipping invalid row. Error: {err}");
-                continue;
-            }
+/// Normalize user input into a [Reference].
+///
+/// Surrounding whitespace is trimmed, letters are uppercased and the separator
+/// between program and number is replaced with a dash. For example
+/// `" onff-0010 "`, `"ONFF 0010"` and `"ONFF_0010"` are all normalized into
+/// `"ONFF-0010"`.
+pub fn normalize_reference(s: &str) -> Option<Reference> {
+    let is_separator = |c: char| c.is_whitespace() || matches!(c, '-' | '_' | '–' | '—');
+
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    let normalized = match s.find(is_separator) {
+        Some(i) => {
+            let (program, number) = (&s[..i], s[i..].trim_start_matches(is_separator));
+            format!("{program}-{number}")
         }
+        None => s.to_string(),
+    };
+
+    Some(Reference::from_str(&normalized).ok()?.to_ascii_uppercase())
+}
+
+/// Check if two strings refer to the same WWFF [Reference].
+///
+/// Both are normalized with [normalize_reference] like in lookups, so e.g.
+/// `"onff 0010"` matches `"ONFF-0010"`. Strings which can't be normalized
+/// don't match anything.
+pub fn matches_reference(a: &str, b: &str) -> bool {
+    match (normalize_reference(a), normalize_reference(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Check if given string looks like a valid WWFF [Reference].
+///
+/// A valid reference consists of an ITU prefix, `FF`, a dash and four digits,
+/// e.g. `ONFF-0010`. Letters are matched case-insensitively. This only checks
+/// the format, not whether the reference exists.
+pub fn is_valid_reference(s: &str) -> bool {
+    let Some((program, number)) = s.split_once('-') else {
+        return false;
+    };
+
+    let Some((prefix, ff)) = program
+        .len()
+        .checked_sub(2)
+        .and_then(|i| Some((program.get(..i)?, program.get(i..)?)))
+    else {
+        return false;
+    };
+
+    ff.eq_ignore_ascii_case("FF")
+        && (1..=4).contains(&prefix.len())
+        && prefix.bytes().all(|b| b.is_ascii_alphanumeric())
+        && number.len() == 4
+        && number.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Get the ITU prefix of a WWFF [Reference], e.g. `ON` of `ONFF-0010`.
+///
+/// Returns [None] if the string isn't a valid reference as checked by
+/// [is_valid_reference].
+pub fn reference_prefix(s: &str) -> Option<&str> {
+    if !is_valid_reference(s) {
+        return None;
+    }
+    s.get(..s.find('-')? - 2)
+}
+
+#[instrument(skip(reader))]
+fn read<T: Row, R: Read>(reader: R, options: &ReadOptions) -> Result<Parsed<T>, WwffError> {
+    // Rows with wrong number of fields are reported by Columns::parse
+    let mut rdr = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(decode(reader)?);
+    let mut parsed = Parsed {
+        map: BTreeMap::new(),
+        report: ParseReport::default(),
+        lines: BTreeMap::new(),
+    };
+    let ts = std::time::Instant::now();
+
+    let columns = Columns::new::<T>(rdr.headers()?, options)?;
+
+    let mut record = csv::StringRecord::new();
+    loop {
+        let row = match rdr.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => match columns.parse(&record) {
+                Some(row) => row,
+                None => continue,
+            },
+            Err(err) => Err(invalid_record(err)?),
+        };
+        parsed.push(row, options);
     }
 
     tracing::debug!(
         "Reading WWFF directory ({} entries) took {} ms.",
-        map.len(),
+        parsed.map.len(),
         ts.elapsed().as_millis()
     );
 
-    Ok(map)
+    Ok(parsed)
+}
+
+/// Headers of WWFF directory CSV file
+const HEADERS: &[&str] = &[
+    "reference",
+    "status",
+    "name",
+    "program",
+    "dxcc",
+    "state",
+    "county",
+    "continent",
+    "iota",
+    "iaruLocator",
+    "latitude",
+    "longitude",
+    "IUCNcat",
+    "validFrom",
+    "validTo",
+    "notes",
+    "lastMod",
+    "changeLog",
+    "reviewFlag",
+    "specialFlags",
+    "website",
+    "country",
+    "region",
+    "dxccEnum",
+    "qsoCount",
+    "lastAct",
+];
+
+/// Header information needed for parsing rows
+struct Columns<'a> {
+    headers: csv::StringRecord,
+    reference: Option<usize>,
+    dxcc: Option<usize>,
+    /// Positions and headers of unknown columns
+    #[cfg(feature = "extra-columns")]
+    extra: Vec<(usize, String)>,
+    options: &'a ReadOptions,
+}
+
+impl<'a> Columns<'a> {
+    fn new<T: Row>(
+        headers: &csv::StringRecord,
+        options: &'a ReadOptions,
+    ) -> Result<Self, WwffError> {
+        // Headers are matched case-insensitively so that changes in case
+        // don't break parsing
+        let headers: Vec<&str> = headers
+            .iter()
+            .map(|h| {
+                let h = h.trim();
+                HEADERS
+                    .iter()
+                    .find(|known| known.eq_ignore_ascii_case(h))
+                    .copied()
+                    .unwrap_or(h)
+            })
+            .collect();
+
+        if let Some(missing) = T::REQUIRED_COLUMNS
+            .iter()
+            .find(|required| !headers.contains(required))
+        {
+            return Err(WwffError::Parse(format!(
+                "Missing required column \"{missing}\""
+            )));
+        }
+
+        Ok(Self {
+            // Columns with unknown headers are ignored by deserializer without
+            // allocating their content
+            headers: headers
+                .iter()
+                .map(|h| if options.skips(h) { "" } else { h })
+                .collect(),
+            reference: headers.iter().position(|h| *h == "reference"),
+            dxcc: headers.iter().position(|h| *h == "dxcc"),
+            #[cfg(feature = "extra-columns")]
+            extra: headers
+                .iter()
+                .enumerate()
+                .filter(|(_, h)| !h.is_empty() && !HEADERS.contains(h) && !options.skips(h))
+                .map(|(i, h)| (i, h.to_string()))
+                .collect(),
+            options,
+        })
+    }
+
+    /// Deserialize a record. Returns [None] if row is filtered out.
+    fn parse<T: Row>(&self, record: &csv::StringRecord) -> Option<RowResult<T>> {
+        let line = record.position().map(|p| p.line());
+        let reference = || {
+            self.reference
+                .and_then(|i| record.get(i))
+                .map(str::to_string)
+        };
+        let skipped = |error: String| SkippedRow {
+            line,
+            reference: reference(),
+            error,
+        };
+
+        if record.len() != self.headers.len() {
+            return Some(Err(skipped(format!(
+                "Expected {} fields but found {}",
+                self.headers.len(),
+                record.len()
+            ))));
+        }
+
+        // Rows filtered out by DXCC are dropped before deserializing
+        if !self
+            .options
+            .keeps_dxcc(self.dxcc.and_then(|i| record.get(i)))
+        {
+            return None;
+        }
+
+        let (row, warnings) =
+            report::collect_field_warnings(|| record.deserialize::<T>(Some(&self.headers)));
+        let row = row.map(|mut row| {
+            if self.options.normalizes_text() {
+                row.normalize_text();
+            }
+            row
+        });
+        match row {
+            Ok(row) if !row.keep(self.options) => None,
+            Ok(row) => {
+                let warnings = warnings
+                    .into_iter()
+                    .map(|w| w.into_warning(line, reference()))
+                    .collect();
+                Some(Ok((line, self.with_extra(row, record), warnings)))
+            }
+            Err(err) => Some(Err(skipped(err.to_string()))),
+        }
+    }
+
+    #[cfg(feature = "extra-columns")]
+    fn with_extra<T: Row>(&self, mut row: T, record: &csv::StringRecord) -> T {
+        if !self.extra.is_empty() {
+            row.set_extra(
+                self.extra
+                    .iter()
+                    .filter_map(|(i, h)| Some((h.clone(), record.get(*i)?.to_string())))
+                    .collect(),
+            );
+        }
+        row
+    }
+
+    #[cfg(not(feature = "extra-columns"))]
+    fn with_extra<T: Row>(&self, row: T, _record: &csv::StringRecord) -> T {
+        row
+    }
+}
+
+/// Turn error of reading a record into a skipped row. IO errors are fatal.
+fn invalid_record(err: csv::Error) -> Result<SkippedRow, WwffError> {
+    if err.is_io_error() {
+        return Err(err.into());
+    }
+    Ok(SkippedRow {
+        line: err.position().map(|p| p.line()),
+        reference: None,
+        error: err.to_string(),
+    })
+}
+
+/// Without gzip support the input is read as is
+#[cfg(not(feature = "gzip"))]
+fn decode<R: Read>(reader: R) -> io::Result<R> {
+    Ok(reader)
+}
+
+/// Read CSV from async reader on a blocking thread
+#[cfg(feature = "async")]
+async fn read_async<R>(reader: R, options: ReadOptions) -> Result<Parsed, WwffError>
+where
+    R: tokio::io::AsyncRead + Send + Unpin + 'static,
+{
+    let reader = tokio_util::io::SyncIoBridge::new(reader);
+    tokio::task::spawn_blocking(move || read(reader, &options))
+        .await
+        .map_err(io::Error::other)?
 }
 
 fn deserialize_f32_opt<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(f32::deserialize(deserializer).ok())
+    /// Coordinate given as number (e.g. JSON) or as text (e.g. CSV)
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Coordinate {
+        Number(f32),
+        Text(String),
+    }
+
+    // Binary formats can't tell numbers from text
+    if !deserializer.is_human_readable() {
+        return Option::<f32>::deserialize(deserializer);
+    }
+
+    Ok(
+        match Option::<Coordinate>::deserialize(deserializer)
+            .ok()
+            .flatten()
+        {
+            Some(Coordinate::Number(n)) => Some(n),
+            Some(Coordinate::Text(s)) => parse_f32(&s),
+            None => None,
+        },
+    )
+}
+
+/// Parse number allowing comma as decimal separator, e.g. `"52,1234"`.
+/// Non-finite values like `"NaN"` or `"inf"` are rejected.
+fn parse_f32(s: &str) -> Option<f32> {
+    if is_placeholder(s) {
+        return None;
+    }
+
+    let s = s.trim();
+    let n = match s.parse() {
+        Ok(n) => Some(n),
+        Err(_) if s.matches(',').count() == 1 => s.replace(',', ".").parse().ok(),
+        Err(_) => None,
+    };
+    if let Some(n) = n.filter(|n: &f32| n.is_finite()) {
+        return Some(n);
+    }
+
+    report::field_warning(ParseWarning::InvalidNumber, s);
+    None
 }
 
 fn deserialize_date_opt<'de, D>(deserializer: D) -> Result<Option<chrono::NaiveDate>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(chrono::NaiveDate::deserialize(deserializer).ok())
+    let Some(s) = Option::<String>::deserialize(deserializer).ok().flatten() else {
+        return Ok(None);
+    };
+
+    // Date may be followed by time, e.g. "2023-01-15 12:00:00"
+    let date = s.trim().split([' ', 'T']).next().unwrap_or_default();
+    Ok(date.parse().ok())
 }
 
 fn deserialize_status<'de, D>(deserializer: D) -> Result<Status, D::Error>
@@ -247,24 +1402,75 @@ where
         "national" => Status::National,
         "proposed" => Status::Proposed,
         _ => {
-            return Err(serde::de::Error::custom(format!(
-                "Unknown WWFF status \"{s}\""
-            )))
+            report::field_warning(ParseWarning::UnknownStatus, &s);
+            Status::Other(s)
         }
     })
 }
 
+fn deserialize_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(mojibake::repair(String::deserialize(deserializer)?))
+}
+
+/// Trim and collapse whitespace into single spaces and strip control and
+/// zero-width characters, e.g. `" Big\u{200b}  Park\t"` into `"Big Park"`
+fn normalize_text(s: &str) -> String {
+    const ZERO_WIDTH: &[char] = &['\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}', '\u{feff}'];
+
+    let mut normalized = String::with_capacity(s.len());
+    let mut space = false;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            space = !normalized.is_empty();
+        } else if !c.is_control() && !ZERO_WIDTH.contains(&c) {
+            if space {
+                normalized.push(' ');
+                space = false;
+            }
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// Check if value is a placeholder for missing value, e.g. `"-"` or `"N/A"`
+fn is_placeholder(s: &str) -> bool {
+    const PLACEHOLDERS: &[&str] = &["", "-", "--", "n/a", "none"];
+    let s = s.trim();
+    PLACEHOLDERS.iter().any(|p| p.eq_ignore_ascii_case(s))
+}
+
 fn deserialize_string_opt<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
 
-    if s.is_empty() || s == "-" || s == "n/a" {
+    if is_placeholder(&s) {
         return Ok(None);
     }
 
-    Ok(Some(s))
+    Ok(Some(mojibake::repair(s)))
+}
+
+/// Serialize as string also in binary formats so that [deserialize_tinystr]
+/// can read the value back
+fn serialize_tinystr<S, const N: usize>(
+    value: &Option<TinyAsciiStr<N>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    value
+        .as_ref()
+        .map(TinyAsciiStr::as_str)
+        .serialize(serializer)
 }
 
 fn deserialize_tinystr<'de, D, const N: usize>(
@@ -273,16 +1479,19 @@ fn deserialize_tinystr<'de, D, const N: usize>(
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
 
-    if s.is_empty() || s == "-" || s == "n/a" {
+    if is_placeholder(&s) {
         return Ok(None);
     }
 
-    // Skip known issue
-    if s == "Región 1" {
+    let s = mojibake::repair(s);
+    let Some(s) = fold_ascii(&s) else {
+        report::field_warning(ParseWarning::NonAsciiValue, &s);
         return Ok(None);
-    }
+    };
 
     if let Ok(s) = TinyAsciiStr::from_str(s.trim()) {
         return Ok(Some(s));
@@ -292,3 +1501,330 @@ where
         "Couldn't deserialize \"{s}\" to TinyAsciiStr"
     )))
 }
+
+/// Replace Latin letters with diacritics by their ASCII base letters, e.g.
+/// `"Región 1"` into `"Region 1"`. Returns [None] if other non-ASCII
+/// characters remain.
+fn fold_ascii(s: &str) -> Option<String> {
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_ascii() {
+            folded.push(c);
+            continue;
+        }
+        folded.push_str(match c {
+            'À'..='Å' => "A",
+            'Æ' => "AE",
+            'Ç' => "C",
+            'È'..='Ë' => "E",
+            'Ì'..='Ï' => "I",
+            'Ð' => "D",
+            'Ñ' => "N",
+            'Ò'..='Ö' | 'Ø' => "O",
+            'Ù'..='Ü' => "U",
+            'Ý' => "Y",
+            'Þ' => "TH",
+            'ß' => "ss",
+            'à'..='å' => "a",
+            'æ' => "ae",
+            'ç' => "c",
+            'è'..='ë' => "e",
+            'ì'..='ï' => "i",
+            'ð' => "d",
+            'ñ' => "n",
+            'ò'..='ö' | 'ø' => "o",
+            'ù'..='ü' => "u",
+            'ý' | 'ÿ' => "y",
+            'þ' => "th",
+            _ => return None,
+        });
+    }
+    Some(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{
+        is_placeholder, is_valid_reference, normalize_reference, normalize_text, parse_f32,
+        DuplicateRow, Entry, InvalidValue, ParseWarning, SkippedRow, Status, WwffDirectory,
+        WwffError,
+    };
+
+    const PLACEHOLDERS: &[&str] = &["", "-", "--", "n/a", "N/A", "none", "NONE", " ", " \t "];
+
+    /// CSV file with a single row with given values overriding the defaults
+    fn csv_row(values: &[(&str, &str)]) -> String {
+        let mut row = vec![
+            ("reference", "ONFF-0010"),
+            ("status", "active"),
+            ("name", "Park"),
+            ("program", "ONFF"),
+            ("dxcc", "ON"),
+            ("state", ""),
+            ("county", ""),
+            ("continent", "EU"),
+            ("reviewFlag", "0"),
+        ];
+        for &(column, value) in values {
+            match row.iter_mut().find(|(c, _)| *c == column) {
+                Some(field) => field.1 = value,
+                None => row.push((column, value)),
+            }
+        }
+        let (header, record): (Vec<_>, Vec<_>) = row.into_iter().unzip();
+        format!("{}\n{}\n", header.join(","), record.join(","))
+    }
+
+    fn parse_row(values: &[(&str, &str)]) -> WwffDirectory {
+        csv_row(values).parse().unwrap()
+    }
+
+    fn entry(directory: &WwffDirectory) -> &Entry {
+        directory.search_reference("ONFF-0010").unwrap()
+    }
+
+    #[test]
+    fn keeps_row_with_unknown_status() {
+        for status in ["averyveryverylongstatusvalue", "päivitetty"] {
+            let directory = parse_row(&[("status", status)]);
+            assert_eq!(entry(&directory).status, Status::Other(status.to_string()));
+        }
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse_f32("52.1234"), Some(52.1234));
+        assert_eq!(parse_f32("52,1234"), Some(52.1234));
+        assert_eq!(parse_f32(" 52.1 "), Some(52.1));
+        assert_eq!(parse_f32("-"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_numbers() {
+        for s in [
+            "1,2,3", "1.2.3", "abc", "NaN", "nan", "inf", "-inf", "infinity",
+        ] {
+            assert_eq!(parse_f32(s), None, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn passes_field_warnings_to_sink() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink = warnings.clone();
+        let csv = csv_row(&[("status", "weird"), ("latitude", "abc"), ("IUCNcat", "Ω")]);
+        let directory = WwffDirectory::builder()
+            .on_warning(move |w| sink.lock().unwrap().push(w.clone()))
+            .from_reader(csv.as_bytes())
+            .unwrap();
+        assert_eq!(entry(&directory).latitude, None);
+
+        let invalid = |value: &str| InvalidValue {
+            line: Some(2),
+            reference: Some("ONFF-0010".to_string()),
+            value: value.to_string(),
+        };
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            [
+                ParseWarning::UnknownStatus(invalid("weird")),
+                ParseWarning::InvalidNumber(invalid("abc")),
+                ParseWarning::NonAsciiValue(invalid("Ω")),
+            ]
+        );
+    }
+
+    #[test]
+    fn normalizes_text() {
+        assert_eq!(normalize_text(" \t Big Park\t "), "Big Park");
+        assert_eq!(normalize_text("Big  \t\n Park"), "Big Park");
+        assert_eq!(
+            normalize_text("\u{feff}Big\u{200b} Park\u{200b}"),
+            "Big Park"
+        );
+        assert_eq!(normalize_text("Big\u{0} Park\u{7}\u{1b}"), "Big Park");
+        assert_eq!(normalize_text("Big\r\nPark"), "Big Park");
+        assert_eq!(normalize_text(" \t\u{200b} "), "");
+    }
+
+    #[test]
+    fn keeps_raw_text() {
+        let name = "\u{feff} Big\u{200b}  Park\t";
+        let csv = csv_row(&[("name", &format!("\"{name}\"")), ("notes", "\"a  b\"")]);
+
+        let directory: WwffDirectory = csv.parse().unwrap();
+        assert_eq!(entry(&directory).name, "Big Park");
+        assert_eq!(entry(&directory).notes, "a b");
+
+        let directory = WwffDirectory::builder()
+            .raw_text()
+            .from_reader(csv.as_bytes())
+            .unwrap();
+        assert_eq!(entry(&directory).name, name);
+        assert_eq!(entry(&directory).notes, "a  b");
+    }
+
+    #[test]
+    fn writes_csv_which_reads_back() {
+        let mut directory = parse_row(&[("latitude", "60.1875"), ("IUCNcat", "II")]);
+        let mut csv = Vec::new();
+        directory.write_csv(&mut csv).unwrap();
+        let read_back = WwffDirectory::from_reader(csv.as_slice()).unwrap();
+        assert_eq!(read_back.sorted_entries(), directory.sorted_entries());
+        assert_eq!(read_back.content_hash(), directory.content_hash());
+
+        directory.retain(|_, _| false);
+        let mut csv = Vec::new();
+        directory.write_csv(&mut csv).unwrap();
+        let read_back = WwffDirectory::from_reader(csv.as_slice()).unwrap();
+        assert!(read_back.sorted_entries().is_empty());
+        assert_eq!(read_back.content_hash(), directory.content_hash());
+    }
+
+    #[test]
+    fn normalizes_references() {
+        for (input, expected) in [
+            ("ONFF-0010", Some("ONFF-0010")),
+            (" onff-0010 ", Some("ONFF-0010")),
+            ("ONFF 0010", Some("ONFF-0010")),
+            ("ONFF_0010", Some("ONFF-0010")),
+            ("ONFF–0010", Some("ONFF-0010")),
+            ("ONFF—0010", Some("ONFF-0010")),
+            ("onff \t- 0010", Some("ONFF-0010")),
+            ("3d2ff-0001", Some("3D2FF-0001")),
+            ("KFF-1234", Some("KFF-1234")),
+            ("ONF-10", Some("ONF-10")),
+            ("ONFF0010", Some("ONFF0010")),
+            ("", None),
+            ("  ", None),
+            ("ÖNFF-0010", None),
+            ("ONFF-0010-0010-0010", None),
+        ] {
+            assert_eq!(
+                normalize_reference(input).as_ref().map(|r| r.as_str()),
+                expected,
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn validates_references() {
+        for valid in [
+            "ONFF-0010",
+            "onff-0010",
+            "KFF-1234",
+            "3D2FF-0001",
+            "VKFF-0000",
+        ] {
+            assert!(is_valid_reference(valid), "{valid:?}");
+        }
+        for invalid in [
+            "",
+            "ONF-10",
+            "ONFF-10",
+            "ONFF-00100",
+            "ONFF0010",
+            "ONFF 0010",
+            " ONFF-0010 ",
+            "FF-0010",
+            "ABCDEFF-0010",
+            "ONXX-0010",
+            "ONFF-00a0",
+            "ÖNFF-0010",
+            "ÖF-0010",
+            "ONFF-００１０",
+        ] {
+            assert!(!is_valid_reference(invalid), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn matches_headers_case_insensitively() {
+        let csv = "CONTINENT,Reference,NAME,status,Program,DXCC,State,County,reviewflag,Latitude\n\
+                   EU,ONFF-0010,Park,active,ONFF,ON,,,0,50.5\n";
+        let directory: WwffDirectory = csv.parse().unwrap();
+        let entry = entry(&directory);
+        assert_eq!(entry.name, "Park");
+        assert_eq!(entry.continent.as_str(), "EU");
+        assert_eq!(entry.latitude, Some(50.5));
+    }
+
+    #[test]
+    fn reports_missing_column() {
+        let csv = "reference,status,program,dxcc,state,county,continent,reviewFlag\n\
+                   ONFF-0010,active,ONFF,ON,,,EU,0\n";
+        let err = csv.parse::<WwffDirectory>().unwrap_err();
+        assert!(
+            matches!(&err, WwffError::Parse(msg) if msg == "Missing required column \"name\""),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn reports_ragged_rows() {
+        let csv = csv_row(&[])
+            + "ONFF-0011,active,Park\n"
+            + "ONFF-0012,active,Park,ONFF,ON,,,EU,0,extra\n";
+        let directory: WwffDirectory = csv.parse().unwrap();
+        assert!(directory.search_reference("ONFF-0010").is_some());
+        assert!(directory.search_reference("ONFF-0011").is_none());
+        assert!(directory.search_reference("ONFF-0012").is_none());
+        assert_eq!(
+            directory.parse_report().skipped_rows,
+            [
+                SkippedRow {
+                    line: Some(3),
+                    reference: Some("ONFF-0011".to_string()),
+                    error: "Expected 9 fields but found 3".to_string(),
+                },
+                SkippedRow {
+                    line: Some(4),
+                    reference: Some("ONFF-0012".to_string()),
+                    error: "Expected 9 fields but found 10".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_duplicates() {
+        let csv = csv_row(&[])
+            + "ONFF-0011,active,Other Park,ONFF,ON,,,EU,0\n"
+            + "onff-0010,deleted,Renamed Park,ONFF,ON,,,EU,0\n";
+        let directory: WwffDirectory = csv.parse().unwrap();
+        let entry = entry(&directory);
+        assert_eq!(entry.name, "Renamed Park");
+        assert_eq!(entry.status, Status::Deleted);
+        assert_eq!(
+            directory.parse_report().duplicates,
+            [DuplicateRow {
+                reference: "ONFF-0010".to_string(),
+                first_line: Some(2),
+                line: Some(4),
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_placeholders() {
+        for s in PLACEHOLDERS {
+            assert!(is_placeholder(s), "{s:?}");
+        }
+        for s in ["0", "n", "-1", "nonexistent", "n/a/b"] {
+            assert!(!is_placeholder(s), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn reads_placeholders_as_missing() {
+        for s in PLACEHOLDERS {
+            let directory = parse_row(&[("website", s), ("IUCNcat", s)]);
+            let entry = entry(&directory);
+            assert_eq!(entry.website, None, "{s:?}");
+            assert_eq!(entry.iucn_category, None, "{s:?}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Directory optimized for point lookups
+
+use std::collections::HashMap;
+
+use tracing::instrument;
+
+use crate::{normalize_reference, Entry, ParseReport, Reference};
+
+/// WWFF directory stored in a [HashMap] for fast lookups by reference
+///
+/// Created with [WwffDirectory::into_lookup](crate::WwffDirectory::into_lookup).
+/// Lookups are O(1) instead of O(log n), but entries are not kept in
+/// reference order and prefix or range queries are not available. Use
+/// [WwffDirectory](crate::WwffDirectory) unless lookups are a bottleneck.
+#[derive(Debug)]
+pub struct LookupDirectory {
+    map: HashMap<Reference, Entry>,
+    report: ParseReport,
+}
+
+impl LookupDirectory {
+    pub(crate) fn new(map: HashMap<Reference, Entry>, report: ParseReport) -> Self {
+        Self { map, report }
+    }
+
+    /// Get report of problems found while reading the directory.
+    pub fn parse_report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Search directory for reference.
+    ///
+    /// The reference is normalized with [normalize_reference] before lookup.
+    #[instrument(skip(self))]
+    pub fn search_reference(&self, s: &str) -> Option<&Entry> {
+        let reference = normalize_reference(s)?;
+        self.map.get(&reference)
+    }
+
+    /// Get entry with given [Reference].
+    ///
+    /// The reference isn't parsed or normalized, only matched
+    /// case-insensitively.
+    pub fn get(&self, reference: &Reference) -> Option<&Entry> {
+        self.map.get(&reference.to_ascii_uppercase())
+    }
+
+    /// Check if directory contains given reference.
+    pub fn contains(&self, reference: &str) -> bool {
+        self.search_reference(reference).is_some()
+    }
+
+    /// Iterate over all entries in arbitrary order.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.map.values()
+    }
+
+    /// Number of entries in the directory.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the directory has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
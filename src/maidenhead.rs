@@ -0,0 +1,152 @@
+//! Maidenhead grid locator conversions
+
+use tinystr::TinyAsciiStr;
+
+/// Convert Maidenhead grid locator to approximate coordinates.
+///
+/// Returns latitude and longitude of the center of the grid square. Locators
+/// of 2, 4, 6 or 8 characters are supported, e.g. `"KP20"` or `"KP20le"`.
+/// Letters are case-insensitive.
+pub fn maidenhead_to_coordinates(locator: &str) -> Option<(f32, f32)> {
+    let locator = locator.as_bytes();
+    if !matches!(locator.len(), 2 | 4 | 6 | 8) {
+        return None;
+    }
+
+    let mut latitude = -90.0;
+    let mut longitude = -180.0;
+    // Size of the grid square in degrees
+    let (mut lat_size, mut lon_size) = (180.0, 360.0);
+
+    for (i, pair) in locator.chunks(2).enumerate() {
+        let (base, divisions) = match i {
+            0 => (b'A', 18),
+            1 | 3 => (b'0', 10),
+            _ => (b'A', 24),
+        };
+
+        lon_size /= f64::from(divisions);
+        lat_size /= f64::from(divisions);
+
+        let lon = pair[0].to_ascii_uppercase().checked_sub(base)?;
+        let lat = pair[1].to_ascii_uppercase().checked_sub(base)?;
+        if lon >= divisions || lat >= divisions {
+            return None;
+        }
+
+        longitude += f64::from(lon) * lon_size;
+        latitude += f64::from(lat) * lat_size;
+    }
+
+    Some((
+        (latitude + lat_size / 2.0) as f32,
+        (longitude + lon_size / 2.0) as f32,
+    ))
+}
+
+/// Convert coordinates to Maidenhead grid locator.
+///
+/// `precision` is the number of characters in the locator and must be 2, 4, 6
+/// or 8. Subsquare letters are returned in lowercase, e.g. `"KP20le"`.
+pub fn coordinates_to_maidenhead(
+    latitude: f32,
+    longitude: f32,
+    precision: usize,
+) -> Option<TinyAsciiStr<12>> {
+    if !matches!(precision, 2 | 4 | 6 | 8)
+        || !(-90.0..=90.0).contains(&latitude)
+        || !(-180.0..=180.0).contains(&longitude)
+    {
+        return None;
+    }
+
+    // Offsets from south-west corner of the world
+    let mut latitude = f64::from(latitude) + 90.0;
+    let mut longitude = f64::from(longitude) + 180.0;
+    let (mut lat_size, mut lon_size) = (180.0, 360.0);
+
+    let mut locator = [0u8; 8];
+    for i in 0..precision / 2 {
+        let (base, divisions) = match i {
+            0 => (b'A', 18),
+            1 | 3 => (b'0', 10),
+            _ => (b'a', 24),
+        };
+
+        lon_size /= f64::from(divisions);
+        lat_size /= f64::from(divisions);
+
+        // North pole and antimeridian are clamped inside the last square
+        let lon = ((longitude / lon_size) as u8).min(divisions - 1);
+        let lat = ((latitude / lat_size) as u8).min(divisions - 1);
+        longitude -= f64::from(lon) * lon_size;
+        latitude -= f64::from(lat) * lat_size;
+
+        locator[i * 2] = base + lon;
+        locator[i * 2 + 1] = base + lat;
+    }
+
+    TinyAsciiStr::from_bytes(&locator[..precision]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coordinates_to_maidenhead, maidenhead_to_coordinates};
+
+    fn assert_near(actual: Option<(f32, f32)>, expected: (f32, f32)) {
+        let (lat, lon) = actual.unwrap();
+        assert!(
+            (lat - expected.0).abs() < 1e-4 && (lon - expected.1).abs() < 1e-4,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn converts_locator_to_coordinates() {
+        assert_near(maidenhead_to_coordinates("KP20le"), (60.1875, 24.9583));
+        assert_near(maidenhead_to_coordinates("kp20LE"), (60.1875, 24.9583));
+        assert_near(maidenhead_to_coordinates("KP"), (65.0, 30.0));
+        assert_near(maidenhead_to_coordinates("KP20"), (60.5, 25.0));
+        assert_near(maidenhead_to_coordinates("KP20le55"), (60.1896, 24.9625));
+    }
+
+    #[test]
+    fn rejects_invalid_locators() {
+        for locator in [
+            "",
+            "K",
+            "KP2",
+            "KP20l",
+            "KP20le5",
+            "KP20le55a",
+            "SP",
+            "KZ",
+            "KPA0",
+            "KP2A",
+            "KP20z0",
+            "KP20le5a",
+            "ÄP",
+        ] {
+            assert_eq!(maidenhead_to_coordinates(locator), None, "{locator:?}");
+        }
+    }
+
+    #[test]
+    fn converts_coordinates_to_locator() {
+        let locator = |lat, lon, precision| {
+            coordinates_to_maidenhead(lat, lon, precision).map(|l| l.to_string())
+        };
+        assert_eq!(locator(60.1875, 24.9583, 6).as_deref(), Some("KP20le"));
+        assert_eq!(locator(60.1875, 24.9583, 2).as_deref(), Some("KP"));
+        assert_eq!(locator(60.1896, 24.9625, 8).as_deref(), Some("KP20le55"));
+        assert_eq!(locator(-90.0, -180.0, 4).as_deref(), Some("AA00"));
+        // North pole and antimeridian are inside the last square
+        assert_eq!(locator(90.0, 180.0, 8).as_deref(), Some("RR99xx99"));
+
+        assert_eq!(locator(60.0, 25.0, 3), None);
+        assert_eq!(locator(60.0, 25.0, 10), None);
+        assert_eq!(locator(90.1, 25.0, 6), None);
+        assert_eq!(locator(60.0, -180.1, 6), None);
+        assert_eq!(locator(f32::NAN, 25.0, 6), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Merging of WWFF directories
+
+use std::collections::btree_map;
+
+use tracing::instrument;
+
+use crate::WwffDirectory;
+
+/// How to resolve entries with the same reference when merging directories
+///
+/// Used with [WwffDirectory::merge].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the entry already in the directory
+    KeepExisting,
+    /// Replace the entry with the one from the other directory
+    Overwrite,
+    /// Keep the entry with the newer `last_modified`. The existing entry is
+    /// kept if both are equally new. Entry without `last_modified` is older
+    /// than any entry with it.
+    KeepNewer,
+}
+
+impl WwffDirectory {
+    /// Merge entries of other directory into this one, e.g. to augment the
+    /// official directory with a supplementary CSV file.
+    ///
+    /// Entries with a reference not yet in this directory are added. For
+    /// entries with a reference already in this directory the given
+    /// [MergeStrategy] decides which one is kept. Problems found while reading
+    /// the other directory are appended to the [parse
+    /// report](WwffDirectory::parse_report).
+    #[instrument(skip(self, other))]
+    pub fn merge(&mut self, other: WwffDirectory, strategy: MergeStrategy) {
+        for (reference, entry) in other.map {
+            match self.map.entry(reference) {
+                btree_map::Entry::Vacant(vacant) => {
+                    vacant.insert(entry);
+                }
+                btree_map::Entry::Occupied(mut occupied) => {
+                    let replace = match strategy {
+                        MergeStrategy::KeepExisting => false,
+                        MergeStrategy::Overwrite => true,
+                        MergeStrategy::KeepNewer => {
+                            entry.last_modified > occupied.get().last_modified
+                        }
+                    };
+                    if replace {
+                        occupied.insert(entry);
+                    }
+                }
+            }
+        }
+
+        self.report.skipped_rows.extend(other.report.skipped_rows);
+        self.report.duplicates.extend(other.report.duplicates);
+    }
+}
@@ -0,0 +1,112 @@
+//! Slim variant of WWFF directory
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tinystr::TinyAsciiStr;
+use tracing::instrument;
+
+use crate::builder::ReadOptions;
+use crate::{normalize_reference, read, ParseReport, Reference, Row, Status, WwffError};
+
+/// WWFF directory containing only [MinimalEntry] values
+///
+/// This uses noticeably less memory than
+/// [WwffDirectory](crate::WwffDirectory) because descriptive fields such as
+/// notes, changelog and website are not kept.
+#[derive(Debug)]
+pub struct MinimalDirectory {
+    map: BTreeMap<Reference, MinimalEntry>,
+    report: ParseReport,
+}
+
+impl MinimalDirectory {
+    /// Read CSV file from given [Path]
+    #[instrument(fields(path = %path.as_ref().to_string_lossy()))]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<MinimalDirectory, WwffError> {
+        let parsed = read(File::open(path)?, &ReadOptions::default())?;
+        Ok(Self {
+            map: parsed.map,
+            report: parsed.report,
+        })
+    }
+
+    /// Read CSV file from given reader
+    #[instrument(skip(reader))]
+    pub fn from_reader<R: Read>(reader: R) -> Result<MinimalDirectory, WwffError> {
+        let parsed = read(reader, &ReadOptions::default())?;
+        Ok(Self {
+            map: parsed.map,
+            report: parsed.report,
+        })
+    }
+
+    /// Get report of problems found while reading the directory.
+    pub fn parse_report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Search directory for reference.
+    ///
+    /// The reference is normalized with [normalize_reference] before lookup.
+    #[instrument(skip(self))]
+    pub fn search_reference(&self, s: &str) -> Option<&MinimalEntry> {
+        let reference = normalize_reference(s)?;
+        self.map.get(&reference)
+    }
+
+    /// Iterate over all entries in reference order.
+    pub fn entries(&self) -> impl Iterator<Item = &MinimalEntry> {
+        self.map.values()
+    }
+
+    /// Number of entries in the directory.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the directory has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// A single WWFF entity entry with only the most commonly used fields
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MinimalEntry {
+    pub reference: Reference,
+    #[serde(deserialize_with = "crate::deserialize_status")]
+    pub status: Status,
+    #[serde(deserialize_with = "crate::deserialize_string")]
+    pub name: String,
+    pub program: TinyAsciiStr<12>,
+    #[serde(default, deserialize_with = "crate::deserialize_f32_opt")]
+    pub latitude: Option<f32>,
+    #[serde(default, deserialize_with = "crate::deserialize_f32_opt")]
+    pub longitude: Option<f32>,
+}
+
+impl Row for MinimalEntry {
+    const REQUIRED_COLUMNS: &'static [&'static str] = &["reference", "status", "name", "program"];
+
+    fn reference(&self) -> Reference {
+        self.reference
+    }
+
+    fn normalize_text(&mut self) {
+        self.name = crate::normalize_text(&self.name);
+    }
+}
+
+/// Read slim WWFF directory from CSV file in given [Path]
+pub fn from_path_minimal<P: AsRef<Path>>(path: P) -> Result<MinimalDirectory, WwffError> {
+    MinimalDirectory::from_path(path)
+}
+
+/// Read slim WWFF directory from given reader
+pub fn from_reader_minimal<R: Read>(reader: R) -> Result<MinimalDirectory, WwffError> {
+    MinimalDirectory::from_reader(reader)
+}
@@ -0,0 +1,116 @@
+//! Repair of double-encoded UTF-8 text
+//!
+//! Text which has been encoded as UTF-8, then decoded using a single byte
+//! encoding and encoded again as UTF-8 turns into so called mojibake. For
+//! example "Región" becomes "RegiÃ³n" through Windows-1252 or "Regi√≥n"
+//! through Mac OS Roman.
+
+/// Windows-1252 characters in range 0x80..=0x9F. Other bytes map to the
+/// Unicode code point of the same value like in Latin-1.
+#[rustfmt::skip]
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+/// Mac OS Roman characters in range 0x80..=0xFF
+#[rustfmt::skip]
+const MAC_ROMAN: [char; 128] = [
+    'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è',
+    'ê', 'ë', 'í', 'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü',
+    '†', '°', '¢', '£', '§', '•', '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø',
+    '∞', '±', '≤', '≥', '¥', 'µ', '∂', '∑', '∏', 'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø',
+    '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«', '»', '…', '\u{a0}', 'À', 'Ã', 'Õ', 'Œ', 'œ',
+    '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ', 'Ÿ', '⁄', '€', '‹', '›', 'ﬁ', 'ﬂ',
+    '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', 'Ó', 'Ô',
+    '\u{f8ff}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚', '¸', '˝', '˛', 'ˇ',
+];
+
+/// Repair double-encoded UTF-8 in given string.
+///
+/// The string is returned as is if it's not recognized as mojibake.
+pub(crate) fn repair(s: String) -> String {
+    if s.is_ascii() {
+        return s;
+    }
+
+    let repaired = reencode(&s, windows_1252_byte).or_else(|| reencode(&s, mac_roman_byte));
+    match repaired {
+        Some(repaired) => {
+            tracing::debug!("Repaired double-encoded text {s:?} into {repaired:?}");
+            repaired
+        }
+        None => s,
+    }
+}
+
+/// Encode string with single byte encoding and decode the bytes as UTF-8.
+///
+/// Returns [None] if some character can't be encoded or the result is not
+/// valid UTF-8.
+fn reencode(s: &str, encode: fn(char) -> Option<u8>) -> Option<String> {
+    let bytes = s.chars().map(encode).collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        // Latin-1 including C1 control characters
+        0..=0xff => Some(c as u8),
+        _ => WINDOWS_1252
+            .iter()
+            .position(|&w| w == c)
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+fn mac_roman_byte(c: char) -> Option<u8> {
+    if c.is_ascii() {
+        return Some(c as u8);
+    }
+    MAC_ROMAN
+        .iter()
+        .position(|&m| m == c)
+        .map(|i| 0x80 + i as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::repair;
+    use crate::WwffDirectory;
+
+    #[test]
+    fn repairs_mac_roman() {
+        assert_eq!(repair("Regi√≥n".to_string()), "Región");
+    }
+
+    #[test]
+    fn repairs_windows_1252() {
+        assert_eq!(repair("RegiÃ³n".to_string()), "Región");
+        assert_eq!(repair("KÃ¶ln â€“ Park".to_string()), "Köln – Park");
+    }
+
+    #[test]
+    fn keeps_valid_text() {
+        assert_eq!(repair("Región".to_string()), "Región");
+        assert_eq!(repair("Ñandú".to_string()), "Ñandú");
+        assert_eq!(repair("Park".to_string()), "Park");
+    }
+
+    #[test]
+    fn keeps_formerly_skipped_tinystr_value() {
+        for iucn in ["Región 1", "Regi√≥n 1", "RegiÃ³n 1"] {
+            let csv = format!(
+                "reference,status,name,program,dxcc,state,county,continent,IUCNcat,reviewFlag\n\
+                 ONFF-0010,active,Park,ONFF,ON,,,EU,{iucn},0\n"
+            );
+            let directory: WwffDirectory = csv.parse().unwrap();
+            let entry = directory.search_reference("ONFF-0010").unwrap();
+            assert_eq!(
+                entry.iucn_category.as_ref().map(|c| c.as_str()),
+                Some("Region 1"),
+                "{iucn}"
+            );
+        }
+    }
+}
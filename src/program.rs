@@ -0,0 +1,93 @@
+//! WWFF national programs
+
+use std::fmt;
+use std::str::FromStr;
+
+use tinystr::{TinyAsciiStr, TinyStrError};
+
+use crate::{Entry, WwffDirectory};
+
+/// A WWFF national program, e.g. `OHFF` for Finland
+///
+/// Constants are provided for some of the well-known programs but any program
+/// code can be created with [str::parse]. Program codes are uppercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Program(TinyAsciiStr<12>);
+
+impl Program {
+    /// Germany
+    pub const DLFF: Program = Program::from_static("DLFF");
+    /// Spain
+    pub const EAFF: Program = Program::from_static("EAFF");
+    /// France
+    pub const FFF: Program = Program::from_static("FFF");
+    /// England
+    pub const GFF: Program = Program::from_static("GFF");
+    /// Italy
+    pub const IFF: Program = Program::from_static("IFF");
+    /// United States
+    pub const KFF: Program = Program::from_static("KFF");
+    /// Austria
+    pub const OEFF: Program = Program::from_static("OEFF");
+    /// Finland
+    pub const OHFF: Program = Program::from_static("OHFF");
+    /// Czech Republic
+    pub const OKFF: Program = Program::from_static("OKFF");
+    /// Belgium
+    pub const ONFF: Program = Program::from_static("ONFF");
+    /// Netherlands
+    pub const PAFF: Program = Program::from_static("PAFF");
+    /// Poland
+    pub const SPFF: Program = Program::from_static("SPFF");
+    /// Canada
+    pub const VEFF: Program = Program::from_static("VEFF");
+    /// Australia
+    pub const VKFF: Program = Program::from_static("VKFF");
+
+    const fn from_static(s: &'static str) -> Self {
+        Program(TinyAsciiStr::from_bytes_lossy(s.as_bytes()))
+    }
+
+    /// Get the program code, e.g. `"OHFF"`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl FromStr for Program {
+    type Err = TinyStrError;
+
+    /// Parse program code. Surrounding whitespace is ignored and the code is
+    /// uppercased.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Program(
+            TinyAsciiStr::from_str(s.trim())?.to_ascii_uppercase(),
+        ))
+    }
+}
+
+impl From<TinyAsciiStr<12>> for Program {
+    fn from(program: TinyAsciiStr<12>) -> Self {
+        Program(program.to_ascii_uppercase())
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Entry {
+    /// Get the [Program] of the entry.
+    pub fn program(&self) -> Program {
+        Program::from(self.program)
+    }
+}
+
+impl WwffDirectory {
+    /// Iterate over entries of given [Program].
+    pub fn entries_by_program(&self, program: Program) -> impl Iterator<Item = &Entry> {
+        self.map.values().filter(move |e| e.program() == program)
+    }
+}
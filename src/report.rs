@@ -0,0 +1,164 @@
+//! Report of problems found while reading WWFF directory
+
+use std::cell::RefCell;
+
+/// Report of problems found while reading WWFF directory
+///
+/// Rows that couldn't be parsed are skipped and recorded here, as are rows with
+/// duplicate reference which overwrite an earlier row. Get the report
+/// with [WwffDirectory::parse_report](crate::WwffDirectory::parse_report).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Rows skipped because they couldn't be parsed
+    pub skipped_rows: Vec<SkippedRow>,
+    /// Rows with a reference already seen earlier in the file
+    pub duplicates: Vec<DuplicateRow>,
+}
+
+impl ParseReport {
+    /// Check if no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.skipped_rows.is_empty() && self.duplicates.is_empty()
+    }
+}
+
+/// A row of CSV file which was skipped because it couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// 1-based line number of the row in CSV file
+    pub line: Option<u64>,
+    /// Reference of the row, if it could be read
+    pub reference: Option<String>,
+    /// Description of the error
+    pub error: String,
+}
+
+/// A row of CSV file whose reference was already seen on an earlier row
+///
+/// The later row replaces the earlier one in the directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRow {
+    /// Reference of the rows
+    pub reference: String,
+    /// 1-based line number of the earlier, overwritten row
+    pub first_line: Option<u64>,
+    /// 1-based line number of the row kept in the directory
+    pub line: Option<u64>,
+}
+
+/// A problem found while reading WWFF directory
+///
+/// Passed to the callback given to
+/// [DirectoryBuilder::on_warning](crate::DirectoryBuilder::on_warning).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseWarning {
+    /// Row was skipped because it couldn't be parsed
+    SkippedRow(SkippedRow),
+    /// Row overwrote an earlier row with the same reference
+    Duplicate(DuplicateRow),
+    /// Number couldn't be parsed and the field was left empty
+    InvalidNumber(InvalidValue),
+    /// Status is not known by this crate and was kept as
+    /// [Status::Other](crate::Status::Other)
+    UnknownStatus(InvalidValue),
+    /// Value had non-ASCII characters which couldn't be replaced and the
+    /// field was left empty
+    NonAsciiValue(InvalidValue),
+}
+
+impl ParseWarning {
+    /// Log warning with `tracing`
+    pub(crate) fn log(&self) {
+        match self {
+            ParseWarning::SkippedRow(skipped) => tracing::error!(
+                line = ?skipped.line,
+                reference = ?skipped.reference,
+                "Skipping invalid row. Error: {}",
+                skipped.error
+            ),
+            ParseWarning::Duplicate(duplicate) => tracing::warn!(
+                first_line = ?duplicate.first_line,
+                line = ?duplicate.line,
+                reference = %duplicate.reference,
+                "Duplicate reference overwrites earlier row"
+            ),
+            ParseWarning::InvalidNumber(invalid) => tracing::warn!(
+                line = ?invalid.line,
+                reference = ?invalid.reference,
+                "Skipping invalid number \"{}\"",
+                invalid.value
+            ),
+            ParseWarning::UnknownStatus(invalid) => tracing::warn!(
+                line = ?invalid.line,
+                reference = ?invalid.reference,
+                "Unknown WWFF status \"{}\"",
+                invalid.value
+            ),
+            ParseWarning::NonAsciiValue(invalid) => tracing::warn!(
+                line = ?invalid.line,
+                reference = ?invalid.reference,
+                "Skipping non-ASCII value \"{}\"",
+                invalid.value
+            ),
+        }
+    }
+}
+
+/// A field value of CSV file which couldn't be parsed as is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidValue {
+    /// 1-based line number of the row in CSV file
+    pub line: Option<u64>,
+    /// Reference of the row
+    pub reference: Option<String>,
+    /// The value as it is in CSV file
+    pub value: String,
+}
+
+/// Warning about a field value found while deserializing a row. Line and
+/// reference are added once the whole row is deserialized.
+pub(crate) struct FieldWarning {
+    kind: fn(InvalidValue) -> ParseWarning,
+    value: String,
+}
+
+impl FieldWarning {
+    pub(crate) fn into_warning(self, line: Option<u64>, reference: Option<String>) -> ParseWarning {
+        (self.kind)(InvalidValue {
+            line,
+            reference,
+            value: self.value,
+        })
+    }
+}
+
+thread_local! {
+    /// Field warnings of the row being deserialized on this thread, see
+    /// [collect_field_warnings]
+    static FIELD_WARNINGS: RefCell<Option<Vec<FieldWarning>>> = const { RefCell::new(None) };
+}
+
+/// Report invalid field value of the row being deserialized, e.g.
+/// `field_warning(ParseWarning::InvalidNumber, "abc")`.
+///
+/// Outside [collect_field_warnings], e.g. when deserializing from another
+/// format than CSV, the warning is logged right away.
+pub(crate) fn field_warning(kind: fn(InvalidValue) -> ParseWarning, value: &str) {
+    let warning = FieldWarning {
+        kind,
+        value: value.to_string(),
+    };
+    FIELD_WARNINGS.with_borrow_mut(|warnings| match warnings {
+        Some(warnings) => warnings.push(warning),
+        None => warning.into_warning(None, None).log(),
+    });
+}
+
+/// Call `f` deserializing a row and collect field warnings reported by it
+pub(crate) fn collect_field_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<FieldWarning>) {
+    let previous = FIELD_WARNINGS.replace(Some(Vec::new()));
+    let result = f();
+    let warnings = FIELD_WARNINGS.replace(previous).unwrap_or_default();
+    (result, warnings)
+}
@@ -0,0 +1,52 @@
+//! Binary snapshot of parsed WWFF directory
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use bincode::Options;
+use tracing::instrument;
+
+use crate::{ParseReport, Parsed, WwffDirectory, WwffError, WwffMap};
+
+/// Version of snapshot format. Bump when [Entry](crate::Entry) changes.
+const SNAPSHOT_VERSION: u32 = 2;
+
+impl WwffDirectory {
+    /// Save parsed directory as a binary snapshot to given [Path].
+    ///
+    /// Load it back later with [WwffDirectory::load_snapshot]. The parse
+    /// report and download state are not saved.
+    #[instrument(skip(self), fields(path = %path.as_ref().to_string_lossy()))]
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<(), WwffError> {
+        let mut w = BufWriter::new(File::create(path)?);
+        bincode::options().serialize_into(&mut w, &(SNAPSHOT_VERSION, &self.map))?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Load directory from binary snapshot saved with
+    /// [WwffDirectory::save_snapshot].
+    ///
+    /// Snapshots saved by an incompatible version of this crate are rejected
+    /// with [WwffError::Parse].
+    #[instrument(fields(path = %path.as_ref().to_string_lossy()))]
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<WwffDirectory, WwffError> {
+        let bytes = std::fs::read(path)?;
+        // Check version before trying to read the rest in unknown format
+        let version: u32 = bincode::options()
+            .allow_trailing_bytes()
+            .deserialize(&bytes)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(WwffError::Parse(format!(
+                "Unsupported snapshot version {version}"
+            )));
+        }
+        let (_, map): (u32, WwffMap) = bincode::options().deserialize(&bytes)?;
+        Ok(WwffDirectory::from_parsed(Parsed {
+            map,
+            report: ParseReport::default(),
+            lines: Default::default(),
+        }))
+    }
+}
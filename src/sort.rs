@@ -0,0 +1,54 @@
+//! Sorting of entry lists
+
+use std::cmp::Reverse;
+
+use crate::Entry;
+
+/// Key for [sort_entries_by]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    /// By reference
+    Reference,
+    /// By name, case-insensitively
+    Name,
+    /// By QSO count, highest first
+    QsoCount,
+    /// By date of last activity, most recent first
+    LastActivity,
+    /// By distance from given point, nearest first
+    Distance { latitude: f32, longitude: f32 },
+}
+
+/// Sort entries by given [SortKey].
+///
+/// Entries without the sorted value, e.g. without QSO count, are placed last.
+/// Entries with equal values are ordered by reference.
+pub fn sort_entries_by(entries: &mut [&Entry], key: SortKey) {
+    match key {
+        SortKey::Reference => entries.sort_by_key(|e| e.reference),
+        SortKey::Name => entries.sort_by_cached_key(|e| (e.name.to_lowercase(), e.reference)),
+        SortKey::QsoCount => {
+            entries.sort_by_key(|e| (e.qso_count.is_none(), Reverse(e.qso_count), e.reference))
+        }
+        SortKey::LastActivity => entries.sort_by_key(|e| {
+            (
+                e.last_activity.is_none(),
+                Reverse(e.last_activity),
+                e.reference,
+            )
+        }),
+        SortKey::Distance {
+            latitude,
+            longitude,
+        } => {
+            let distance = |e: &Entry| e.distance_from(latitude, longitude);
+            entries.sort_by(|a, b| {
+                match (distance(a), distance(b)) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (a, b) => a.is_none().cmp(&b.is_none()),
+                }
+                .then_with(|| a.reference.cmp(&b.reference))
+            });
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Grouping and statistics over WWFF directory
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use tinystr::TinyAsciiStr;
+
+use crate::{coordinates_to_maidenhead, maidenhead_to_coordinates, Entry, Status, WwffDirectory};
+
+/// Summary statistics of WWFF directory
+///
+/// Created with [WwffDirectory::statistics].
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct DirectoryStats {
+    /// Total number of entries
+    pub entries: usize,
+    /// Number of entries per status
+    pub by_status: BTreeMap<Status, usize>,
+    /// Number of entries with explicit latitude and longitude
+    pub with_coordinates: usize,
+    /// Number of entries with a valid IARU locator
+    pub with_valid_locator: usize,
+    /// Number of entries per continent. Entries without continent are not
+    /// counted.
+    pub by_continent: BTreeMap<TinyAsciiStr<2>, usize>,
+    /// The newest `last_modified` of all entries
+    pub newest_last_modified: Option<NaiveDate>,
+}
+
+impl WwffDirectory {
+    /// Compute summary statistics of the directory in a single pass.
+    pub fn statistics(&self) -> DirectoryStats {
+        let mut stats = DirectoryStats::default();
+
+        for e in self.map.values() {
+            stats.entries += 1;
+            *stats.by_status.entry(e.status.clone()).or_default() += 1;
+            if e.latitude.is_some() && e.longitude.is_some() {
+                stats.with_coordinates += 1;
+            }
+            if e.iaru_locator
+                .is_some_and(|l| maidenhead_to_coordinates(&l).is_some())
+            {
+                stats.with_valid_locator += 1;
+            }
+            if !e.continent.is_empty() {
+                *stats.by_continent.entry(e.continent).or_default() += 1;
+            }
+            stats.newest_last_modified = stats.newest_last_modified.max(e.last_modified);
+        }
+
+        stats
+    }
+
+    /// Group entries by their continent.
+    ///
+    /// Entries without continent are skipped.
+    pub fn by_continent(&self) -> BTreeMap<TinyAsciiStr<2>, Vec<&Entry>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for entry in self.map.values().filter(|e| !e.continent.is_empty()) {
+            groups.entry(entry.continent).or_default().push(entry);
+        }
+        groups
+    }
+
+    /// Count entries per program.
+    ///
+    /// Programs are compared case-insensitively and returned in uppercase.
+    pub fn program_counts(&self) -> BTreeMap<TinyAsciiStr<12>, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in self.map.values() {
+            *counts
+                .entry(entry.program.to_ascii_uppercase())
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    /// Count entries per Maidenhead grid square of given `precision`, e.g. 4
+    /// for `"KP20"` or 6 for `"KP20le"`.
+    ///
+    /// The grid square is derived from `iaru_locator` if it's precise enough
+    /// and otherwise from coordinates. Entries without either are skipped.
+    /// Returns an empty map if precision is not 2, 4 or 6.
+    pub fn count_by_grid(&self, precision: usize) -> BTreeMap<TinyAsciiStr<6>, usize> {
+        let mut counts = BTreeMap::new();
+        if !matches!(precision, 2 | 4 | 6) {
+            return counts;
+        }
+
+        for entry in self.map.values() {
+            if let Some(grid) = grid_square(entry, precision) {
+                *counts.entry(grid.resize()).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Get the program codes appearing under each DXCC.
+    ///
+    /// Entries without DXCC are skipped.
+    pub fn programs_by_dxcc(&self) -> BTreeMap<TinyAsciiStr<8>, BTreeSet<TinyAsciiStr<12>>> {
+        let mut groups: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for entry in self.map.values().filter(|e| !e.dxcc.is_empty()) {
+            groups.entry(entry.dxcc).or_default().insert(entry.program);
+        }
+        groups
+    }
+
+    /// Get `n` entries with the highest QSO count, highest first.
+    ///
+    /// Entries without QSO count are skipped. Entries with equal QSO count are
+    /// ordered by reference.
+    pub fn top_by_qso_count(&self, n: usize) -> Vec<&Entry> {
+        let mut entries: Vec<_> = self
+            .map
+            .values()
+            .filter(|e| e.qso_count.is_some())
+            .collect();
+        // Stable sort keeps reference order for equal QSO counts
+        entries.sort_by_key(|e| Reverse(e.qso_count));
+        entries.truncate(n);
+        entries
+    }
+}
+
+/// Grid square of entry with given precision
+fn grid_square(entry: &Entry, precision: usize) -> Option<TinyAsciiStr<12>> {
+    let from_locator = entry
+        .iaru_locator
+        .and_then(|l| maidenhead_to_coordinates(l.get(..precision)?));
+    match from_locator {
+        // Convert back to get canonical case of the locator
+        Some((latitude, longitude)) => coordinates_to_maidenhead(latitude, longitude, precision),
+        None => entry.maidenhead(precision),
+    }
+}
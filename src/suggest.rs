@@ -0,0 +1,119 @@
+//! Suggestions for mistyped references
+
+use crate::{normalize_reference, Reference, WwffDirectory};
+
+/// Maximum edit distance of suggested references
+const MAX_DISTANCE: usize = 2;
+
+impl WwffDirectory {
+    /// Suggest at most `max` references close to `s`, e.g. `ONFF-0010` for
+    /// mistyped `ONFF-0011`.
+    ///
+    /// References within Levenshtein distance of two are returned, closest
+    /// first. `s` is normalized with [normalize_reference] like in
+    /// [WwffDirectory::search_reference]. Only references with the same
+    /// program as `s` are considered, unless there are none in which case
+    /// all references are.
+    pub fn suggest(&self, s: &str, max: usize) -> Vec<&Reference> {
+        let query = match normalize_reference(s) {
+            Some(reference) => reference.to_string(),
+            None => s.trim().to_ascii_uppercase(),
+        };
+
+        let program = query.split_once('-').map(|(program, _)| program);
+        let mut candidates: Vec<(usize, &Reference)> = match program {
+            Some(program) => suggestions(&query, self.references_with_program(program)),
+            None => Vec::new(),
+        };
+        if candidates.is_empty() {
+            candidates = suggestions(&query, self.map.keys());
+        }
+
+        candidates.sort();
+        candidates
+            .into_iter()
+            .take(max)
+            .map(|(_, reference)| reference)
+            .collect()
+    }
+
+    /// Range scan over references of given program
+    fn references_with_program<'a>(&'a self, program: &str) -> impl Iterator<Item = &'a Reference> {
+        let start = Reference::from_str(&format!("{program}-")).ok();
+        start.into_iter().flat_map(move |start| {
+            self.map
+                .range(start..)
+                .map(|(reference, _)| reference)
+                .take_while(move |reference| reference.starts_with(start.as_str()))
+        })
+    }
+}
+
+/// References within [MAX_DISTANCE] of query together with their distance
+fn suggestions<'a>(
+    query: &str,
+    references: impl Iterator<Item = &'a Reference>,
+) -> Vec<(usize, &'a Reference)> {
+    references
+        .map(|reference| (levenshtein(query, reference), reference))
+        .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+        .collect()
+}
+
+/// Levenshtein distance between two ASCII strings
+fn levenshtein(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WwffDirectory;
+
+    fn directory(references: &[&str]) -> WwffDirectory {
+        let mut csv =
+            String::from("reference,status,name,program,dxcc,state,county,continent,reviewFlag\n");
+        for reference in references {
+            csv.push_str(&format!("{reference},active,Park,,,,,EU,0\n"));
+        }
+        csv.parse().unwrap()
+    }
+
+    #[test]
+    fn suggests_references_of_same_program() {
+        let directory = directory(&["OHFF-0011", "ONFF-0001", "ONFF-0012"]);
+        for query in ["ONFF-0011", "onff-0011", "onff 0011", " ONFF_0011 "] {
+            let suggestions: Vec<_> = directory
+                .suggest(query, 5)
+                .into_iter()
+                .map(|r| r.as_str())
+                .collect();
+            assert_eq!(suggestions, ["ONFF-0001", "ONFF-0012"], "{query:?}");
+        }
+    }
+
+    #[test]
+    fn suggests_any_program_if_none_match() {
+        let directory = directory(&["OHFF-0011", "DLFF-0100"]);
+        let suggestions: Vec<_> = directory
+            .suggest("OGFF-0011", 5)
+            .into_iter()
+            .map(|r| r.as_str())
+            .collect();
+        assert_eq!(suggestions, ["OHFF-0011"]);
+    }
+}
@@ -0,0 +1,77 @@
+//! Consistency checks of WWFF directory entries
+
+use crate::{maidenhead_to_coordinates, reference_prefix, Entry, Reference, WwffDirectory};
+
+/// An inconsistency found in an [Entry]
+///
+/// Created with [WwffDirectory::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Reference of the entry
+    pub reference: Reference,
+    /// Human-readable description of the issue
+    pub description: String,
+}
+
+impl WwffDirectory {
+    /// Check that entries are internally consistent.
+    ///
+    /// Flags coordinates out of range, `valid_to` earlier than `valid_from`,
+    /// IARU locator not matching the coordinates and reference prefix not
+    /// matching `dxcc`.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for entry in self.map.values() {
+            let mut issue = |description: String| {
+                issues.push(ValidationIssue {
+                    reference: entry.reference,
+                    description,
+                })
+            };
+
+            if let Some(latitude) = entry.latitude.filter(|lat| !(-90.0..=90.0).contains(lat)) {
+                issue(format!("Latitude {latitude} out of range"));
+            }
+            if let Some(longitude) = entry
+                .longitude
+                .filter(|lon| !(-180.0..=180.0).contains(lon))
+            {
+                issue(format!("Longitude {longitude} out of range"));
+            }
+            if let (Some(from), Some(to)) = (entry.valid_from, entry.valid_to) {
+                if to < from {
+                    issue(format!("Valid to {to} is earlier than valid from {from}"));
+                }
+            }
+            if let Some(description) = check_locator(entry) {
+                issue(description);
+            }
+            if let Some(prefix) = reference_prefix(&entry.reference) {
+                if !entry.dxcc.is_empty() && !prefix.eq_ignore_ascii_case(&entry.dxcc) {
+                    issue(format!(
+                        "Reference prefix {prefix} doesn't match DXCC {}",
+                        entry.dxcc
+                    ));
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Check that IARU locator is valid and matches the coordinates
+fn check_locator(entry: &Entry) -> Option<String> {
+    let locator = entry.iaru_locator?;
+    if maidenhead_to_coordinates(&locator).is_none() {
+        return Some(format!("Invalid IARU locator {locator}"));
+    }
+
+    let computed = entry.maidenhead(locator.len())?;
+    if computed.eq_ignore_ascii_case(&locator) {
+        return None;
+    }
+    Some(format!(
+        "IARU locator {locator} doesn't match coordinates ({}, {}) which are in {computed}",
+        entry.latitude?, entry.longitude?
+    ))
+}